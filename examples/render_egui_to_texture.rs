@@ -0,0 +1,157 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+    },
+};
+use bevy_egui::{egui, EguiContext, EguiContexts, EguiPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(EguiPlugin::default())
+        .init_resource::<RotationSpeed>()
+        .add_startup_system(setup)
+        .add_system(texture_ui_system)
+        .add_system(rotator_system)
+        .run();
+}
+
+// Marks the camera that renders Egui into the texture.
+#[derive(Component)]
+struct TextureCamera;
+
+// Marks the quad that displays the texture.
+#[derive(Component)]
+struct Rotator;
+
+#[derive(Resource)]
+struct RotationSpeed(f32);
+
+impl Default for RotationSpeed {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let size = Extent3d {
+        width: 512,
+        height: 512,
+        ..default()
+    };
+
+    // This is the texture that Egui will be rendered to.
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image_handle = images.add(image);
+
+    // The camera doesn't render anything itself, it only clears the texture to transparent
+    // before the Egui context, which is attached to it, is drawn on top.
+    commands.spawn((
+        Camera2dBundle {
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+            },
+            camera: Camera {
+                // Render before the main pass camera.
+                order: -1,
+                target: RenderTarget::Image(image_handle.clone()),
+                ..default()
+            },
+            ..default()
+        },
+        EguiContext::default(),
+        TextureCamera,
+    ));
+
+    // Egui writes colors premultiplied by alpha, so the texture is blended as such.
+    let quad_material = materials.add(StandardMaterial {
+        base_color_texture: Some(image_handle),
+        alpha_mode: AlphaMode::Premultiplied,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(4.0)))),
+            material: quad_material,
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+        Rotator,
+    ));
+
+    // A cube behind the quad, which can be seen through the transparent parts of the UI.
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 2.0 })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        transform: Transform::from_xyz(0.0, 0.0, -2.0),
+        ..default()
+    });
+
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 4.0, 6.0),
+        ..default()
+    });
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 0.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
+
+fn texture_ui_system(
+    mut contexts: EguiContexts,
+    mut rotation_speed: ResMut<RotationSpeed>,
+    texture_camera: Query<Entity, With<TextureCamera>>,
+) {
+    // Pointer input isn't passed to contexts that are rendered into images, so the widgets
+    // are driven by the window's context below.
+    let ctx = contexts.ctx_for_window_mut(texture_camera.single());
+    egui::Window::new("Egui on a quad")
+        .fixed_pos([32.0, 32.0])
+        .show(ctx, |ui| {
+            ui.label("The texture is transparent outside of this window.");
+            ui.label(format!("Rotation speed: {:.2}", rotation_speed.0));
+        });
+
+    egui::Window::new("Controls").show(contexts.ctx_mut(), |ui| {
+        ui.add(egui::Slider::new(&mut rotation_speed.0, 0.0..=5.0).text("Rotation speed"));
+    });
+}
+
+fn rotator_system(
+    time: Res<Time>,
+    rotation_speed: Res<RotationSpeed>,
+    mut query: Query<&mut Transform, With<Rotator>>,
+) {
+    for mut transform in &mut query {
+        transform.rotate_y(rotation_speed.0 * time.delta_seconds());
+    }
+}
@@ -15,7 +15,7 @@ use egui::Widget;
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugin(EguiPlugin)
+        .add_plugin(EguiPlugin::default())
         .add_startup_system(setup)
         .add_system(rotator_system)
         .add_system(render_to_image_example_system)
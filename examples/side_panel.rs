@@ -17,7 +17,7 @@ struct OriginalCameraTransform(Transform);
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugin(EguiPlugin)
+        .add_plugin(EguiPlugin::default())
         .init_resource::<OccupiedScreenSpace>()
         .add_startup_system(setup_system)
         .add_system(ui_example_system)
@@ -13,7 +13,7 @@ struct Images {
 fn main() {
     let mut app = App::new();
     app.add_plugins(DefaultPlugins)
-        .add_plugin(EguiPlugin)
+        .add_plugin(EguiPlugin::default())
         .init_resource::<SharedUiState>()
         .add_startup_system(load_assets_system)
         .add_startup_system(create_new_window_system)
@@ -26,7 +26,7 @@ fn main() {
         .insert_resource(Msaa::Sample4)
         .init_resource::<UiState>()
         .add_plugins(DefaultPlugins)
-        .add_plugin(EguiPlugin)
+        .add_plugin(EguiPlugin::default())
         .add_startup_system(configure_visuals_system)
         .add_startup_system(configure_ui_state_system)
         .add_system(update_ui_scale_factor_system)
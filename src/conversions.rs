@@ -0,0 +1,62 @@
+use bevy::render::color::Color;
+
+/// Converts a Bevy color to an Egui color.
+///
+/// Bevy colors can be stored in any color space (sRGB, linear or HSL), while [`egui::Color32`]
+/// is sRGB with premultiplied alpha, so the color is encoded to sRGB before it's quantized.
+#[must_use]
+pub fn bevy_color_to_egui(color: Color) -> egui::Color32 {
+    let [r, g, b, a] = color.as_rgba_f32().map(|c| (c * 255.0).round() as u8);
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Converts an Egui color to a Bevy color (in the sRGB color space, [`Color::as_rgba_linear`]
+/// can be used to get its linear components).
+///
+/// [`egui::Color32`] stores premultiplied alpha, so the color components of translucent colors
+/// lose some precision.
+#[must_use]
+pub fn egui_color_to_bevy(color: egui::Color32) -> Color {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    Color::rgba_u8(r, g, b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bevy_color_to_egui, egui_color_to_bevy};
+    use bevy::render::color::Color;
+
+    #[test]
+    fn test_color_conversions() {
+        assert_eq!(bevy_color_to_egui(Color::RED), egui::Color32::RED);
+        assert_eq!(
+            bevy_color_to_egui(Color::RED.as_rgba_linear()),
+            egui::Color32::RED
+        );
+        // 50% gray in linear space is 188 in sRGB, 50% gray in sRGB is 128.
+        assert_eq!(
+            bevy_color_to_egui(Color::rgb_linear(0.5, 0.5, 0.5)),
+            egui::Color32::from_gray(188)
+        );
+        assert_eq!(
+            bevy_color_to_egui(Color::rgb(0.5, 0.5, 0.5)),
+            egui::Color32::from_gray(128)
+        );
+        assert_eq!(
+            bevy_color_to_egui(Color::rgba(1.0, 0.0, 0.0, 0.5)),
+            egui::Color32::from_rgba_unmultiplied(255, 0, 0, 128)
+        );
+
+        assert_eq!(
+            egui_color_to_bevy(egui::Color32::RED),
+            Color::rgb(1.0, 0.0, 0.0)
+        );
+        let [r, g, b, a] = egui_color_to_bevy(egui::Color32::from_gray(188)).as_linear_rgba_f32();
+        assert!((r - 0.5).abs() < 0.01 && r == g && g == b);
+        assert_eq!(a, 1.0);
+        assert_eq!(
+            egui_color_to_bevy(egui::Color32::from_rgba_unmultiplied(255, 0, 0, 128)),
+            Color::rgba_u8(255, 0, 0, 128)
+        );
+    }
+}
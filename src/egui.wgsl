@@ -1,6 +1,8 @@
 struct Transform {
     scale: vec2<f32>,
     translation: vec2<f32>,
+    hdr_scale: f32,
+    tint: vec4<f32>,
 }
 
 struct VertexInput {
@@ -26,6 +28,13 @@ fn linear_from_srgb(srgb: vec3<f32>) -> vec3<f32> {
     return select(higher, lower, cutoff);
 }
 
+fn gamma_from_linear(linear: vec3<f32>) -> vec3<f32> {
+    let cutoff = linear < vec3<f32>(0.0031308);
+    let lower = linear * 12.92;
+    let higher = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(higher, lower, cutoff);
+}
+
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
     let position = in.position * transform.scale + transform.translation;
@@ -37,7 +46,29 @@ fn vs_main(in: VertexInput) -> VertexOutput {
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let texture_color = textureSample(image_texture, image_sampler, in.uv);
     // This assumes that texture images are not premultiplied.
-    let color = in.color * vec4<f32>(texture_color.rgb * texture_color.a, texture_color.a);
+    var color = in.color * vec4<f32>(texture_color.rgb * texture_color.a, texture_color.a) * transform.tint;
+
+#ifdef STRAIGHT_ALPHA
+    // Blending multiplies the colors by alpha itself.
+    color = vec4<f32>(select(color.rgb / color.a, vec3<f32>(0.0), color.a <= 0.0), color.a);
+#endif
 
+#ifdef GAMMA_OUTPUT
+    // The target format doesn't encode colors, so we do it ourselves.
+#ifdef STRAIGHT_ALPHA
+    return vec4<f32>(gamma_from_linear(color.rgb), color.a);
+#else
+    // Premultiplied colors have to be encoded unpremultiplied. Colors with zero alpha are
+    // additive and are encoded as is.
+    if color.a <= 0.0 {
+        return vec4<f32>(gamma_from_linear(color.rgb), color.a);
+    }
+    return vec4<f32>(gamma_from_linear(color.rgb / color.a) * color.a, color.a);
+#endif
+#else ifdef HDR_OUTPUT
+    // Extended-range targets: scale white to the configured luminance.
+    return vec4<f32>(color.rgb * transform.hdr_scale, color.a);
+#else
     return color;
+#endif
 }
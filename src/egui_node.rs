@@ -1,33 +1,43 @@
 use crate::{
     render_systems::{
-        EguiPipelines, EguiTextureBindGroups, EguiTextureId, EguiTransform, EguiTransforms,
-        ExtractedEguiSettings,
+        EguiCapturePipeline, EguiCapturedImages, EguiDrawCalls, EguiPendingCapture,
+        EguiPendingCaptures, EguiPipelines, EguiTextureBindGroups, EguiTextureId, EguiTransform,
+        EguiTransforms, ExtractedEguiSettings,
     },
-    EguiRenderOutput, WindowSize,
+    EguiBlendMode, EguiCaptureRequest, EguiCustomShader, EguiRenderOrder, EguiRenderOutput,
+    EguiScissorRounding, EguiViewport, WindowSize,
 };
 use bevy::{
     core::cast_slice,
     ecs::world::{FromWorld, World},
-    prelude::{Entity, HandleUntyped, Resource},
+    math::{Rect, Vec2},
+    prelude::{Color, Entity, Handle, HandleUntyped, Resource},
     reflect::TypeUuid,
     render::{
+        camera::{ExtractedCamera, NormalizedRenderTarget},
+        render_asset::RenderAssets,
         render_graph::{Node, NodeRunError, RenderGraphContext},
         render_resource::{
             AddressMode, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
             BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, Buffer,
             BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState,
-            ColorWrites, Extent3d, FragmentState, FrontFace, IndexFormat, LoadOp, MultisampleState,
-            Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
-            RenderPassDescriptor, RenderPipelineDescriptor, SamplerBindingType, SamplerDescriptor,
-            Shader, ShaderStages, ShaderType, SpecializedRenderPipeline, TextureDimension,
-            TextureFormat, TextureSampleType, TextureViewDimension, VertexBufferLayout,
-            VertexFormat, VertexState, VertexStepMode,
+            ColorWrites, Extent3d, FragmentState, FrontFace, ImageCopyBuffer, ImageDataLayout,
+            IndexFormat, LoadOp, MultisampleState, Operations, PipelineCache, PrimitiveState,
+            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor,
+            SamplerBindingType, SamplerDescriptor, Shader, ShaderStages, ShaderType,
+            SpecializedRenderPipeline, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+            TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor,
+            TextureViewDimension, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
         },
         renderer::{RenderContext, RenderDevice, RenderQueue},
         texture::{Image, ImageSampler},
         view::ExtractedWindows,
     },
 };
+use std::{num::NonZeroU32, ops::Range};
+
+/// Format of the textures captured Egui contexts are drawn into.
+pub const EGUI_CAPTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
 
 /// Egui shader.
 pub const EGUI_SHADER_HANDLE: HandleUntyped =
@@ -51,7 +61,7 @@ impl FromWorld for EguiPipeline {
                 label: Some("egui transform bind group layout"),
                 entries: &[BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: ShaderStages::VERTEX,
+                    visibility: ShaderStages::VERTEX_FRAGMENT,
                     ty: BindingType::Buffer {
                         ty: BufferBindingType::Uniform,
                         has_dynamic_offset: true,
@@ -92,16 +102,66 @@ impl FromWorld for EguiPipeline {
 }
 
 /// Key for specialized pipeline.
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone)]
 pub struct EguiPipelineKey {
     /// Texture format of a window's swap chain to render to.
+    ///
+    /// The shader outputs linear colors, which are encoded by the GPU for sRGB formats. For
+    /// non-sRGB 8-bit formats (such as `Rgba8Unorm` or `Bgra8Unorm` surfaces), the shader
+    /// gamma-encodes the output itself, so that colors match Egui's reference look. Other formats
+    /// (for example, `Rgba16Float` render-to-texture targets) are treated as linear and receive
+    /// linear colors, which are expected to be encoded when the texture is displayed. Floating
+    /// point formats are treated as HDR, see [`EguiPipelineKey::hdr_output`].
     pub texture_format: TextureFormat,
+    /// Replaces the fragment stage of the built-in shader, see [`EguiCustomShader`].
+    pub custom_shader: Option<EguiCustomShader>,
+    /// Blending of the rendered context, see [`EguiBlendMode`].
+    pub blend_mode: EguiBlendMode,
+}
+
+impl EguiPipelineKey {
+    /// Whether the target format is floating point, which is treated as HDR, see
+    /// [`crate::EguiSettings::hdr_paper_white`].
+    pub fn hdr_output(&self) -> bool {
+        matches!(
+            self.texture_format,
+            TextureFormat::Rgba16Float | TextureFormat::Rgba32Float | TextureFormat::Rg11b10Float
+        )
+    }
+
+    /// Whether the shader needs to gamma-encode its output for the target format.
+    pub fn gamma_output(&self) -> bool {
+        matches!(
+            self.texture_format,
+            TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm | TextureFormat::Rgb10a2Unorm
+        )
+    }
 }
 
 impl SpecializedRenderPipeline for EguiPipeline {
     type Key = EguiPipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let (fragment_shader, fragment_entry_point) = match &key.custom_shader {
+            Some(custom_shader) => (
+                custom_shader.shader.clone(),
+                custom_shader.fragment_entry_point.clone(),
+            ),
+            None => (EGUI_SHADER_HANDLE.typed(), "fs_main".into()),
+        };
+        let mut shader_defs = Vec::new();
+        if key.gamma_output() {
+            shader_defs.push("GAMMA_OUTPUT".into());
+        } else if key.hdr_output() {
+            shader_defs.push("HDR_OUTPUT".into());
+        }
+        let color_src_factor = match key.blend_mode {
+            EguiBlendMode::Premultiplied => BlendFactor::One,
+            EguiBlendMode::Straight => {
+                shader_defs.push("STRAIGHT_ALPHA".into());
+                BlendFactor::SrcAlpha
+            }
+        };
         RenderPipelineDescriptor {
             label: Some("egui render pipeline".into()),
             layout: vec![
@@ -122,14 +182,14 @@ impl SpecializedRenderPipeline for EguiPipeline {
                 )],
             },
             fragment: Some(FragmentState {
-                shader: EGUI_SHADER_HANDLE.typed(),
-                shader_defs: Vec::new(),
-                entry_point: "fs_main".into(),
+                shader: fragment_shader,
+                shader_defs,
+                entry_point: fragment_entry_point,
                 targets: vec![Some(ColorTargetState {
                     format: key.texture_format,
                     blend: Some(BlendState {
                         color: BlendComponent {
-                            src_factor: BlendFactor::One,
+                            src_factor: color_src_factor,
                             dst_factor: BlendFactor::OneMinusSrcAlpha,
                             operation: BlendOperation::Add,
                         },
@@ -156,147 +216,431 @@ impl SpecializedRenderPipeline for EguiPipeline {
 
 #[derive(Debug)]
 struct DrawCommand {
-    vertices_count: usize,
+    indices: Range<u32>,
     egui_texture: EguiTextureId,
     clipping_zone: (u32, u32, u32, u32), // x, y, w, h
 }
 
+/// Draw commands of a single Egui context.
+#[derive(Debug)]
+struct ContextBatch {
+    context_entity: Entity,
+    draw_commands: Range<usize>,
+    capture: Option<CaptureTarget>,
+}
+
+/// Texture a captured context is additionally drawn into, and the buffer it's copied to for
+/// reading back.
+#[derive(Debug)]
+struct CaptureTarget {
+    texture: Texture,
+    view: TextureView,
+    buffer: Buffer,
+    size: Extent3d,
+    padded_bytes_per_row: u32,
+}
+
+impl CaptureTarget {
+    fn new(render_device: &RenderDevice, width: u32, height: u32) -> Self {
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some("egui capture texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: EGUI_CAPTURE_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        let padded_bytes_per_row = padded_bytes_per_row(width);
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("egui capture buffer"),
+            size: padded_bytes_per_row as BufferAddress * height as BufferAddress,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            texture,
+            view,
+            buffer,
+            size,
+            padded_bytes_per_row,
+        }
+    }
+}
+
+/// Converts a clip rect in points to a rect in physical pixels of the target, rounded to whole
+/// pixels. Clip rects are relative to the viewport and can't exceed it.
+fn clip_rect_to_physical(
+    clip_rect: egui::Rect,
+    viewport: Rect,
+    scale_factor: f32,
+    rounding: EguiScissorRounding,
+) -> Rect {
+    let min = (viewport.min + Vec2::new(clip_rect.min.x, clip_rect.min.y) * scale_factor)
+        .max(viewport.min);
+    let max = (viewport.min + Vec2::new(clip_rect.max.x, clip_rect.max.y) * scale_factor)
+        .min(viewport.max);
+    match rounding {
+        EguiScissorRounding::Nearest => Rect {
+            min: min.round(),
+            max: max.round(),
+        },
+        EguiScissorRounding::Outward => Rect {
+            min: min.floor(),
+            max: max.ceil(),
+        },
+    }
+}
+
+/// Clamps a clipping zone (`x, y, w, h`) to the size of the render target, which can differ from
+/// the size the draw commands were prepared for if the window was resized in between. Returns
+/// [`None`] if the zone lies outside of the target.
+fn clamp_scissor_rect(
+    (x, y, w, h): (u32, u32, u32, u32),
+    target_width: u32,
+    target_height: u32,
+) -> Option<(u32, u32, u32, u32)> {
+    if x >= target_width || y >= target_height {
+        return None;
+    }
+    Some((
+        x,
+        y,
+        w.min(target_width - x).max(1),
+        h.min(target_height - y).max(1),
+    ))
+}
+
+/// Returns the number of bytes in a row of an RGBA texture copied to a buffer, padded to
+/// the 256 bytes alignment required by wgpu.
+// `u32::div_ceil` requires a newer compiler than the one supported by Bevy 0.10.
+#[allow(clippy::manual_div_ceil)]
+fn padded_bytes_per_row(width: u32) -> u32 {
+    const COPY_BYTES_PER_ROW_ALIGNMENT: u32 = 256;
+    (4 * width + COPY_BYTES_PER_ROW_ALIGNMENT - 1) / COPY_BYTES_PER_ROW_ALIGNMENT
+        * COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+/// Contexts sharing the same render target, which are drawn within a single render pass.
+#[derive(Debug)]
+struct TargetPass {
+    window_entity: Entity,
+    /// Image the pass draws into, is [`None`] for windows.
+    image: Option<Handle<Image>>,
+    contexts: Vec<ContextBatch>,
+}
+
+/// A GPU buffer that is reused across frames and is reallocated only when its capacity needs
+/// to grow.
+///
+/// The contents are uploaded with [`RenderQueue::write_buffer`], which stages the data and
+/// schedules the copy before the next submission, so the same buffer can be safely rewritten
+/// each frame while the previous one is still in flight.
+///
+/// Is generic over the buffer type only so that the reuse can be tested without a GPU.
+struct GrowableBuffer<B = Buffer> {
+    label: &'static str,
+    usage: BufferUsages,
+    capacity: usize,
+    buffer: Option<B>,
+}
+
+impl<B> GrowableBuffer<B> {
+    fn new(label: &'static str, usage: BufferUsages) -> Self {
+        Self {
+            label,
+            usage: usage | BufferUsages::COPY_DST,
+            capacity: 0,
+            buffer: None,
+        }
+    }
+
+    /// Makes sure the buffer fits `size` bytes, calling `create_buffer` only if it's too small.
+    fn reserve_with(&mut self, size: usize, create_buffer: impl FnOnce(&BufferDescriptor) -> B) {
+        if let Some(capacity) = grown_capacity(self.capacity, size) {
+            self.capacity = capacity;
+            self.buffer = Some(create_buffer(&BufferDescriptor {
+                label: Some(self.label),
+                size: capacity as BufferAddress,
+                usage: self.usage,
+                mapped_at_creation: false,
+            }));
+        }
+    }
+
+    fn buffer(&self) -> Option<&B> {
+        self.buffer.as_ref()
+    }
+}
+
+impl GrowableBuffer {
+    /// Makes sure the buffer fits `size` bytes, reallocating it only if it's too small.
+    fn reserve(&mut self, render_device: &RenderDevice, size: usize) {
+        self.reserve_with(size, |descriptor| render_device.create_buffer(descriptor));
+    }
+}
+
+/// Returns the new capacity if the `current` one can't fit `required` bytes.
+fn grown_capacity(current: usize, required: usize) -> Option<usize> {
+    (required > current).then(|| required.next_power_of_two())
+}
+
 /// Egui render node.
+///
+/// Renders all the Egui contexts, batching the ones that share the same target window into
+/// a single render pass.
+///
+/// The node runs after the cameras, when their multisampled textures are already resolved
+/// into the window and image targets, so it always renders with a single sample, regardless of
+/// the [`Msaa`](bevy::prelude::Msaa) setting (including changes at runtime). Egui meshes are
+/// anti-aliased by the tessellator itself (see [`egui::epaint::TessellationOptions::feathering`]).
 pub struct EguiNode {
-    window_entity: Entity,
     vertex_data: Vec<u8>,
-    vertex_buffer_capacity: usize,
-    vertex_buffer: Option<Buffer>,
+    vertex_buffer: GrowableBuffer,
     index_data: Vec<u8>,
-    index_buffer_capacity: usize,
-    index_buffer: Option<Buffer>,
+    index_buffer: GrowableBuffer,
     draw_commands: Vec<DrawCommand>,
+    target_passes: Vec<TargetPass>,
 }
 
 impl EguiNode {
     /// Constructs Egui render node.
-    pub fn new(window_entity: Entity) -> Self {
+    pub fn new() -> Self {
         EguiNode {
-            window_entity,
             draw_commands: Vec::new(),
+            target_passes: Vec::new(),
             vertex_data: Vec::new(),
-            vertex_buffer_capacity: 0,
-            vertex_buffer: None,
+            vertex_buffer: GrowableBuffer::new("egui vertex buffer", BufferUsages::VERTEX),
             index_data: Vec::new(),
-            index_buffer_capacity: 0,
-            index_buffer: None,
+            index_buffer: GrowableBuffer::new("egui index buffer", BufferUsages::INDEX),
+        }
+    }
+
+    /// Adds a context to the pass of its render target. Contexts that have nothing to draw
+    /// are skipped (unless they are captured), so that targets without any shapes don't begin
+    /// render passes at all.
+    fn push_context_batch(
+        &mut self,
+        window_entity: Entity,
+        image: Option<Handle<Image>>,
+        context_batch: ContextBatch,
+    ) {
+        if context_batch.draw_commands.is_empty() && context_batch.capture.is_none() {
+            return;
+        }
+        match self.target_passes.last_mut() {
+            Some(target_pass) if target_pass.window_entity == window_entity => {
+                target_pass.contexts.push(context_batch);
+            }
+            _ => self.target_passes.push(TargetPass {
+                window_entity,
+                image,
+                contexts: vec![context_batch],
+            }),
         }
     }
 }
 
+impl Default for EguiNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Passes a failed capture to the main world, so that [`crate::EguiCaptureFailed`] is sent.
+fn report_failed_capture(world: &World, window: Entity) {
+    let (Some(pending_captures), Some(captured_images)) = (
+        world.get_resource::<EguiPendingCaptures>(),
+        world.get_resource::<EguiCapturedImages>(),
+    ) else {
+        return;
+    };
+    pending_captures.in_flight.lock().unwrap().insert(window);
+    captured_images.0.lock().unwrap().push((window, None));
+}
+
 impl Node for EguiNode {
     fn update(&mut self, world: &mut World) {
-        let mut window_sizes = world.query::<(&WindowSize, &mut EguiRenderOutput)>();
-
-        let Ok((window_size, mut render_output)) = window_sizes.get_mut(world, self.window_entity) else {
-            return;
-        };
-        let window_size = *window_size;
-        let paint_jobs = std::mem::take(&mut render_output.paint_jobs);
+        let mut render_outputs = world.query::<(
+            Entity,
+            &WindowSize,
+            &mut EguiRenderOutput,
+            Option<&EguiViewport>,
+            Option<&EguiCaptureRequest>,
+            Option<&ExtractedCamera>,
+            Option<&EguiRenderOrder>,
+        )>();
+
+        let mut contexts = render_outputs
+            .iter_mut(world)
+            .map(
+                |(
+                    context_entity,
+                    window_size,
+                    mut render_output,
+                    viewport,
+                    capture_request,
+                    camera,
+                    render_order,
+                )| {
+                    let viewport = match viewport {
+                        Some(viewport) => viewport.physical_rect(*window_size),
+                        None => Rect::new(
+                            0.0,
+                            0.0,
+                            window_size.physical_width,
+                            window_size.physical_height,
+                        ),
+                    };
+                    // Contexts attached to cameras draw into their targets, windows have their
+                    // own contexts as well, so the context entity is the target window entity
+                    // otherwise.
+                    let (window_entity, image) =
+                        match camera.and_then(|camera| camera.target.as_ref()) {
+                            Some(NormalizedRenderTarget::Image(image)) => {
+                                (context_entity, Some(image.clone_weak()))
+                            }
+                            Some(NormalizedRenderTarget::Window(window)) => (window.entity(), None),
+                            None => (context_entity, None),
+                        };
+                    (
+                        render_order.copied().unwrap_or_default(),
+                        window_entity,
+                        context_entity,
+                        (*window_size, viewport, capture_request.is_some(), image),
+                        std::mem::take(&mut render_output.paint_jobs),
+                    )
+                },
+            )
+            .collect::<Vec<_>>();
+        // Render passes load the contents of their targets, so contexts are drawn over the ones
+        // with a lesser render order. Within the same order, sorting by the target makes contexts
+        // sharing a render target adjacent, so that they can be batched into a single render
+        // pass. Sorting by the context entity keeps the draw order deterministic.
+        contexts.sort_by_key(|(render_order, window_entity, context_entity, _, _)| {
+            (*render_order, *window_entity, *context_entity)
+        });
 
         let egui_settings = &world.get_resource::<ExtractedEguiSettings>().unwrap();
 
         let render_device = world.get_resource::<RenderDevice>().unwrap();
 
-        let scale_factor = window_size.scale_factor * egui_settings.scale_factor as f32;
-        if window_size.physical_width == 0.0 || window_size.physical_height == 0.0 {
-            return;
-        }
-
         let mut index_offset = 0;
 
         self.draw_commands.clear();
+        self.target_passes.clear();
         self.vertex_data.clear();
         self.index_data.clear();
 
-        for egui::epaint::ClippedPrimitive {
-            clip_rect,
-            primitive,
-        } in &paint_jobs
+        for (
+            _,
+            window_entity,
+            context_entity,
+            (window_size, viewport, capture, image),
+            paint_jobs,
+        ) in contexts
         {
-            let mesh = match primitive {
-                egui::epaint::Primitive::Mesh(mesh) => mesh,
-                egui::epaint::Primitive::Callback(_) => {
-                    unimplemented!("Paint callbacks aren't supported")
+            let scale_factor =
+                window_size.scale_factor * egui_settings.effective_scale_factor(window_size);
+            if window_size.physical_width == 0.0 || window_size.physical_height == 0.0 {
+                // There's nothing to capture in a zero-sized window, so the request fails.
+                if capture {
+                    report_failed_capture(world, context_entity);
                 }
-            };
+                continue;
+            }
 
-            let (x, y, w, h) = (
-                (clip_rect.min.x * scale_factor).round() as u32,
-                (clip_rect.min.y * scale_factor).round() as u32,
-                (clip_rect.width() * scale_factor).round() as u32,
-                (clip_rect.height() * scale_factor).round() as u32,
-            );
+            let draw_commands_start = self.draw_commands.len();
 
-            if w < 1
-                || h < 1
-                || x >= window_size.physical_width as u32
-                || y >= window_size.physical_height as u32
+            for egui::epaint::ClippedPrimitive {
+                clip_rect,
+                primitive,
+            } in &paint_jobs
             {
-                continue;
-            }
+                let mesh = match primitive {
+                    egui::epaint::Primitive::Mesh(mesh) => mesh,
+                    egui::epaint::Primitive::Callback(_) => {
+                        unimplemented!("Paint callbacks aren't supported")
+                    }
+                };
 
-            self.vertex_data
-                .extend_from_slice(cast_slice::<_, u8>(mesh.vertices.as_slice()));
-            let indices_with_offset = mesh
-                .indices
-                .iter()
-                .map(|i| i + index_offset)
-                .collect::<Vec<_>>();
-            self.index_data
-                .extend_from_slice(cast_slice(indices_with_offset.as_slice()));
-            index_offset += mesh.vertices.len() as u32;
-
-            let texture_handle = match mesh.texture_id {
-                egui::TextureId::Managed(id) => EguiTextureId::Managed(self.window_entity, id),
-                egui::TextureId::User(id) => EguiTextureId::User(id),
-            };
+                let Rect {
+                    min: clip_min,
+                    max: clip_max,
+                } = clip_rect_to_physical(
+                    *clip_rect,
+                    viewport,
+                    scale_factor,
+                    egui_settings.scissor_rounding,
+                );
+                let (x, y, w, h) = (
+                    clip_min.x as u32,
+                    clip_min.y as u32,
+                    (clip_max.x - clip_min.x) as u32,
+                    (clip_max.y - clip_min.y) as u32,
+                );
 
-            let x_viewport_clamp = (x + w).saturating_sub(window_size.physical_width as u32);
-            let y_viewport_clamp = (y + h).saturating_sub(window_size.physical_height as u32);
-            self.draw_commands.push(DrawCommand {
-                vertices_count: mesh.indices.len(),
-                egui_texture: texture_handle,
-                clipping_zone: (
-                    x,
-                    y,
-                    w.saturating_sub(x_viewport_clamp).max(1),
-                    h.saturating_sub(y_viewport_clamp).max(1),
-                ),
-            });
-        }
+                if w < 1
+                    || h < 1
+                    || x >= window_size.physical_width as u32
+                    || y >= window_size.physical_height as u32
+                {
+                    continue;
+                }
 
-        if self.vertex_data.len() > self.vertex_buffer_capacity {
-            self.vertex_buffer_capacity = if self.vertex_data.len().is_power_of_two() {
-                self.vertex_data.len()
-            } else {
-                self.vertex_data.len().next_power_of_two()
-            };
-            self.vertex_buffer = Some(render_device.create_buffer(&BufferDescriptor {
-                label: Some("egui vertex buffer"),
-                size: self.vertex_buffer_capacity as BufferAddress,
-                usage: BufferUsages::COPY_DST | BufferUsages::VERTEX,
-                mapped_at_creation: false,
-            }));
-        }
-        if self.index_data.len() > self.index_buffer_capacity {
-            self.index_buffer_capacity = if self.index_data.len().is_power_of_two() {
-                self.index_data.len()
-            } else {
-                self.index_data.len().next_power_of_two()
+                self.vertex_data
+                    .extend_from_slice(cast_slice::<_, u8>(mesh.vertices.as_slice()));
+                let indices_start = (self.index_data.len() / std::mem::size_of::<u32>()) as u32;
+                for index in &mesh.indices {
+                    self.index_data
+                        .extend_from_slice(&(index + index_offset).to_ne_bytes());
+                }
+                index_offset += mesh.vertices.len() as u32;
+
+                let texture_handle = match mesh.texture_id {
+                    egui::TextureId::Managed(id) => EguiTextureId::Managed(context_entity, id),
+                    egui::TextureId::User(id) => EguiTextureId::User(id),
+                };
+
+                let x_viewport_clamp = (x + w).saturating_sub(window_size.physical_width as u32);
+                let y_viewport_clamp = (y + h).saturating_sub(window_size.physical_height as u32);
+                self.draw_commands.push(DrawCommand {
+                    indices: indices_start..(indices_start + mesh.indices.len() as u32),
+                    egui_texture: texture_handle,
+                    clipping_zone: (
+                        x,
+                        y,
+                        w.saturating_sub(x_viewport_clamp).max(1),
+                        h.saturating_sub(y_viewport_clamp).max(1),
+                    ),
+                });
+            }
+
+            let context_batch = ContextBatch {
+                context_entity,
+                draw_commands: draw_commands_start..self.draw_commands.len(),
+                capture: capture.then(|| {
+                    CaptureTarget::new(
+                        render_device,
+                        window_size.physical_width as u32,
+                        window_size.physical_height as u32,
+                    )
+                }),
             };
-            self.index_buffer = Some(render_device.create_buffer(&BufferDescriptor {
-                label: Some("egui index buffer"),
-                size: self.index_buffer_capacity as BufferAddress,
-                usage: BufferUsages::COPY_DST | BufferUsages::INDEX,
-                mapped_at_creation: false,
-            }));
+            self.push_context_batch(window_entity, image, context_batch);
         }
+
+        self.vertex_buffer
+            .reserve(render_device, self.vertex_data.len());
+        self.index_buffer
+            .reserve(render_device, self.index_data.len());
     }
 
     fn run(
@@ -309,26 +653,25 @@ impl Node for EguiNode {
         let pipeline_cache = world.get_resource::<PipelineCache>().unwrap();
 
         let extracted_windows = &world.get_resource::<ExtractedWindows>().unwrap().windows;
-        let extracted_window =
-            if let Some(extracted_window) = extracted_windows.get(&self.window_entity) {
-                extracted_window
-            } else {
-                return Ok(()); // No window
-            };
-
-        let swap_chain_texture =
-            if let Some(swap_chain_texture) = extracted_window.swap_chain_texture.as_ref() {
-                swap_chain_texture
-            } else {
-                return Ok(()); // No swapchain texture
-            };
+        let gpu_images = world.get_resource::<RenderAssets<Image>>().unwrap();
 
         let render_queue = world.get_resource::<RenderQueue>().unwrap();
 
-        let (vertex_buffer, index_buffer) = match (&self.vertex_buffer, &self.index_buffer) {
-            (Some(vertex), Some(index)) => (vertex, index),
-            _ => return Ok(()),
-        };
+        let mut draw_calls = world.get_resource::<EguiDrawCalls>().map(|draw_calls| {
+            let mut draw_calls = draw_calls.0.lock().unwrap();
+            draw_calls.clear();
+            draw_calls
+        });
+
+        if self.target_passes.is_empty() {
+            return Ok(());
+        }
+
+        let (vertex_buffer, index_buffer) =
+            match (self.vertex_buffer.buffer(), self.index_buffer.buffer()) {
+                (Some(vertex), Some(index)) => (vertex, index),
+                _ => return Ok(()),
+            };
 
         render_queue.write_buffer(vertex_buffer, 0, &self.vertex_data);
         render_queue.write_buffer(index_buffer, 0, &self.index_data);
@@ -336,73 +679,164 @@ impl Node for EguiNode {
         let bind_groups = &world.get_resource::<EguiTextureBindGroups>().unwrap();
 
         let egui_transforms = world.get_resource::<EguiTransforms>().unwrap();
+        let Some((_, transform_buffer_bind_group)) = egui_transforms.bind_group.as_ref() else {
+            return Ok(());
+        };
 
-        let mut render_pass =
-            render_context
-                .command_encoder()
-                .begin_render_pass(&RenderPassDescriptor {
-                    label: Some("egui render pass"),
-                    color_attachments: &[Some(RenderPassColorAttachment {
-                        view: swap_chain_texture,
-                        resolve_target: None,
-                        ops: Operations {
-                            load: LoadOp::Load,
-                            store: true,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                });
-
-        let Some(pipeline_id) = egui_pipelines.get(&extracted_window.entity) else { return Ok(()) };
-        let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else { return Ok(()) };
-
-        render_pass.set_pipeline(pipeline);
-        render_pass.set_vertex_buffer(0, *self.vertex_buffer.as_ref().unwrap().slice(..));
-        render_pass.set_index_buffer(
-            *self.index_buffer.as_ref().unwrap().slice(..),
-            IndexFormat::Uint32,
-        );
+        let capture_pipeline = world
+            .get_resource::<EguiCapturePipeline>()
+            .and_then(|capture_pipeline| pipeline_cache.get_render_pipeline(capture_pipeline.0));
+        let pending_captures = world.get_resource::<EguiPendingCaptures>();
+
+        for target_pass in &self.target_passes {
+            let (target_view, physical_width, physical_height) = match &target_pass.image {
+                Some(image) => {
+                    let Some(gpu_image) = gpu_images.get(image) else {
+                        continue; // The image isn't prepared yet
+                    };
+                    (
+                        &gpu_image.texture_view,
+                        gpu_image.size.x as u32,
+                        gpu_image.size.y as u32,
+                    )
+                }
+                None => {
+                    let Some(extracted_window) = extracted_windows.get(&target_pass.window_entity)
+                    else {
+                        continue; // No window
+                    };
+                    let Some(swap_chain_texture) = extracted_window.swap_chain_texture.as_ref()
+                    else {
+                        continue; // No swapchain texture
+                    };
+                    (
+                        swap_chain_texture,
+                        extracted_window.physical_width,
+                        extracted_window.physical_height,
+                    )
+                }
+            };
+            let Some(pipeline_id) = egui_pipelines.get(&target_pass.window_entity) else {
+                continue;
+            };
+            let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else {
+                continue;
+            };
 
-        let transform_buffer_offset = egui_transforms.offsets[&self.window_entity];
-        let transform_buffer_bind_group = &egui_transforms.bind_group.as_ref().unwrap().1;
-        render_pass.set_bind_group(0, transform_buffer_bind_group, &[transform_buffer_offset]);
+            // The window pass draws all the contexts, while each capture pass draws only
+            // the captured context into its own texture.
+            let mut passes = vec![(target_view, LoadOp::Load, pipeline, None)];
+            if let Some(capture_pipeline) = capture_pipeline {
+                for context in &target_pass.contexts {
+                    if let Some(capture) = &context.capture {
+                        passes.push((
+                            &capture.view,
+                            LoadOp::Clear(Color::NONE.into()),
+                            capture_pipeline,
+                            Some(context.context_entity),
+                        ));
+                    }
+                }
+            }
 
-        let mut vertex_offset: u32 = 0;
-        for draw_command in &self.draw_commands {
-            if draw_command.clipping_zone.0 < extracted_window.physical_width
-                && draw_command.clipping_zone.1 < extracted_window.physical_height
-            {
-                let texture_bind_group = match bind_groups.get(&draw_command.egui_texture) {
-                    Some(texture_resource) => texture_resource,
-                    None => {
-                        vertex_offset += draw_command.vertices_count as u32;
+            for (view, load, pipeline, captured_context) in passes {
+                let mut render_pass =
+                    render_context
+                        .command_encoder()
+                        .begin_render_pass(&RenderPassDescriptor {
+                            label: Some("egui render pass"),
+                            color_attachments: &[Some(RenderPassColorAttachment {
+                                view,
+                                resolve_target: None,
+                                ops: Operations { load, store: true },
+                            })],
+                            depth_stencil_attachment: None,
+                        });
+
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_vertex_buffer(0, *vertex_buffer.slice(..));
+                render_pass.set_index_buffer(*index_buffer.slice(..), IndexFormat::Uint32);
+
+                for context in &target_pass.contexts {
+                    if captured_context.is_some_and(|entity| entity != context.context_entity) {
                         continue;
                     }
-                };
-
-                render_pass.set_bind_group(1, texture_bind_group, &[]);
+                    let draw_commands = &self.draw_commands[context.draw_commands.clone()];
+                    let Some(&transform_buffer_offset) =
+                        egui_transforms.offsets.get(&context.context_entity)
+                    else {
+                        continue;
+                    };
+                    render_pass.set_bind_group(
+                        0,
+                        transform_buffer_bind_group,
+                        &[transform_buffer_offset],
+                    );
+                    let mut context_draw_calls = 0;
+
+                    for draw_command in draw_commands {
+                        let Some((x, y, w, h)) = clamp_scissor_rect(
+                            draw_command.clipping_zone,
+                            physical_width,
+                            physical_height,
+                        ) else {
+                            continue;
+                        };
+
+                        let Some(texture_bind_group) = bind_groups.get(&draw_command.egui_texture)
+                        else {
+                            continue;
+                        };
+
+                        render_pass.set_bind_group(1, texture_bind_group, &[]);
+
+                        render_pass.set_scissor_rect(x, y, w, h);
+
+                        render_pass.draw_indexed(draw_command.indices.clone(), 0, 0..1);
+                        context_draw_calls += 1;
+                    }
 
-                render_pass.set_scissor_rect(
-                    draw_command.clipping_zone.0,
-                    draw_command.clipping_zone.1,
-                    draw_command.clipping_zone.2.min(
-                        extracted_window
-                            .physical_width
-                            .saturating_sub(draw_command.clipping_zone.0),
-                    ),
-                    draw_command.clipping_zone.3.min(
-                        extracted_window
-                            .physical_height
-                            .saturating_sub(draw_command.clipping_zone.1),
-                    ),
-                );
+                    if captured_context.is_none() {
+                        if let Some(draw_calls) = draw_calls.as_mut() {
+                            draw_calls.insert(context.context_entity, context_draw_calls);
+                        }
+                    }
+                }
+            }
 
-                render_pass.draw_indexed(
-                    vertex_offset..(vertex_offset + draw_command.vertices_count as u32),
-                    0,
-                    0..1,
+            for context in &target_pass.contexts {
+                let (Some(capture), Some(_), Some(pending_captures)) =
+                    (&context.capture, capture_pipeline, pending_captures)
+                else {
+                    continue;
+                };
+                render_context.command_encoder().copy_texture_to_buffer(
+                    capture.texture.as_image_copy(),
+                    ImageCopyBuffer {
+                        buffer: &capture.buffer,
+                        layout: ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: NonZeroU32::new(capture.padded_bytes_per_row),
+                            rows_per_image: None,
+                        },
+                    },
+                    capture.size,
                 );
-                vertex_offset += draw_command.vertices_count as u32;
+                pending_captures
+                    .in_flight
+                    .lock()
+                    .unwrap()
+                    .insert(context.context_entity);
+                pending_captures
+                    .captures
+                    .lock()
+                    .unwrap()
+                    .push(EguiPendingCapture {
+                        window: context.context_entity,
+                        buffer: capture.buffer.clone(),
+                        size: capture.size,
+                        padded_bytes_per_row: capture.padded_bytes_per_row,
+                    });
             }
         }
 
@@ -424,15 +858,19 @@ fn alpha_image_as_color_image(image: &egui::FontImage) -> egui::ColorImage {
     }
 }
 
-pub(crate) fn color_image_as_bevy_image(egui_image: &egui::ColorImage) -> Image {
-    let pixels = egui_image
+pub(crate) fn color_image_pixels(egui_image: &egui::ColorImage) -> Vec<u8> {
+    egui_image
         .pixels
         .iter()
         // We unmultiply Egui textures to premultiply them later in the fragment shader.
         // As user textures loaded as Bevy assets are not premultiplied (and there seems to be no
         // convenient way to convert them to premultiplied ones), we do the this with Egui ones.
         .flat_map(|color| color.to_srgba_unmultiplied())
-        .collect();
+        .collect()
+}
+
+pub(crate) fn color_image_as_bevy_image(egui_image: &egui::ColorImage) -> Image {
+    let pixels = color_image_pixels(egui_image);
 
     Image {
         sampler_descriptor: ImageSampler::Descriptor(SamplerDescriptor {
@@ -452,3 +890,130 @@ pub(crate) fn color_image_as_bevy_image(egui_image: &egui::ColorImage) -> Image
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clamp_scissor_rect, clip_rect_to_physical, padded_bytes_per_row, ContextBatch, EguiNode,
+        GrowableBuffer,
+    };
+    use crate::EguiScissorRounding;
+    use bevy::{math::Rect, prelude::Entity, render::render_resource::BufferUsages};
+
+    #[test]
+    fn test_buffer_reuse_across_frames() {
+        // A 10k-vertex UI, with the number of vertices fluctuating a bit between frames.
+        const VERTEX_SIZE: usize = std::mem::size_of::<egui::epaint::Vertex>();
+        let frames = (0..1000).map(|frame| (9_000 + frame * 7 % 1_000) * VERTEX_SIZE);
+
+        // Buffers are identified by the number of allocations made before them.
+        let mut buffer = GrowableBuffer::<usize>::new("test buffer", BufferUsages::VERTEX);
+        let mut allocations = 0;
+        for size in frames {
+            buffer.reserve_with(size, |descriptor| {
+                assert!(descriptor.size as usize >= size);
+                allocations += 1;
+                allocations
+            });
+        }
+
+        // Reallocating every frame would result in 1000 allocations.
+        assert_eq!(allocations, 1);
+        assert_eq!(buffer.buffer(), Some(&1));
+
+        // A frame that doesn't fit replaces the buffer.
+        buffer.reserve_with(20_000 * VERTEX_SIZE, |_| 2);
+        assert_eq!(buffer.buffer(), Some(&2));
+    }
+
+    #[test]
+    fn test_empty_context_skipped() {
+        let mut node = EguiNode::new();
+        let window = Entity::from_raw(0);
+        let batch = |context_entity, draw_commands| ContextBatch {
+            context_entity,
+            draw_commands,
+            capture: None,
+        };
+
+        node.push_context_batch(window, None, batch(Entity::from_raw(1), 0..0));
+        assert!(node.target_passes.is_empty());
+
+        node.push_context_batch(window, None, batch(Entity::from_raw(2), 0..3));
+        node.push_context_batch(window, None, batch(Entity::from_raw(3), 3..3));
+        assert_eq!(node.target_passes.len(), 1);
+        assert_eq!(node.target_passes[0].contexts.len(), 1);
+        assert_eq!(
+            node.target_passes[0].contexts[0].context_entity,
+            Entity::from_raw(2)
+        );
+    }
+
+    #[test]
+    fn test_scissor_clamped_after_resize() {
+        // Draw commands prepared for a 1920x1080 window, which shrinks to 800x600 before
+        // the render pass.
+        let (width, height) = (800, 600);
+        for zone in [
+            (0, 0, 1920, 1080),
+            (700, 500, 400, 300),
+            (799, 599, 1, 1),
+            (900, 100, 100, 100),
+            (100, 700, 100, 100),
+        ] {
+            if let Some((x, y, w, h)) = clamp_scissor_rect(zone, width, height) {
+                assert!(w >= 1 && h >= 1);
+                assert!(x + w <= width && y + h <= height, "{zone:?}");
+            }
+        }
+        assert_eq!(
+            clamp_scissor_rect((700, 500, 400, 300), width, height),
+            Some((700, 500, 100, 100))
+        );
+        assert_eq!(
+            clamp_scissor_rect((900, 100, 100, 100), width, height),
+            None
+        );
+    }
+
+    #[test]
+    fn test_scissor_rounding_fractional_scale() {
+        let pixels_per_point = 1.5;
+        let viewport = Rect::new(0.0, 0.0, 1200.0, 900.0);
+        let clip_rects = [
+            egui::Rect::from_min_max(egui::pos2(10.3, 20.3), egui::pos2(100.3, 200.3)),
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(800.0, 600.0)),
+            egui::Rect::from_min_max(egui::pos2(33.1, 0.5), egui::pos2(34.0, 1.0)),
+        ];
+        for clip_rect in clip_rects {
+            let exact_min = clip_rect.min.to_vec2() * pixels_per_point;
+            let exact_max = clip_rect.max.to_vec2() * pixels_per_point;
+            let outward = clip_rect_to_physical(
+                clip_rect,
+                viewport,
+                pixels_per_point,
+                EguiScissorRounding::Outward,
+            );
+            // No partially covered pixel is clipped off.
+            assert!(outward.min.x <= exact_min.x && outward.min.y <= exact_min.y);
+            assert!(outward.max.x >= exact_max.x && outward.max.y >= exact_max.y);
+            assert!(outward.max.x <= viewport.max.x && outward.max.y <= viewport.max.y);
+            assert!(outward.max.x - outward.min.x < exact_max.x - exact_min.x + 2.0);
+        }
+        let nearest = clip_rect_to_physical(
+            clip_rects[0],
+            viewport,
+            pixels_per_point,
+            EguiScissorRounding::Nearest,
+        );
+        assert_eq!(nearest, Rect::new(15.0, 30.0, 150.0, 300.0));
+    }
+
+    #[test]
+    fn test_capture_row_padding() {
+        assert_eq!(padded_bytes_per_row(1), 256);
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+        assert_eq!(padded_bytes_per_row(1920), 7680);
+    }
+}
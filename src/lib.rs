@@ -16,6 +16,9 @@
 //! `bevy_egui` can be compiled with using only `bevy` and `egui` as dependencies: `manage_clipboard` and `open_url` features,
 //! that require additional crates, can be disabled.
 //!
+//! Apps that use reactive `WinitSettings` update modes can enable the `reactive_repaint` feature, so that they wake up
+//! when Egui requests a delayed repaint (for example, for animations or a blinking text cursor).
+//!
 //! ## Usage
 //!
 //! Here's a minimal usage example:
@@ -27,7 +30,7 @@
 //! fn main() {
 //!     App::new()
 //!         .add_plugins(DefaultPlugins)
-//!         .add_plugin(EguiPlugin)
+//!         .add_plugin(EguiPlugin::default())
 //!         // Systems that create Egui widgets should be run during the `CoreSet::Update` set,
 //!         // or after the `EguiSet::BeginFrame` system (which belongs to the `CoreSet::PreUpdate` set).
 //!         .add_system(ui_example_system)
@@ -47,10 +50,17 @@
 //! cargo run --example ui
 //! ```
 //!
+//! Egui frames are painted only by the render app after the main app update, so painting outside
+//! the Egui schedule (for example, from a system that blocks the update during a long load) isn't
+//! supported, even when frames are run manually with [`EguiManualFrames`]. Move long-running work
+//! to a task instead, so that frames keep being rendered.
+//!
 //! ## See also
 //!
 //! - [`bevy-inspector-egui`](https://github.com/jakobhellermann/bevy-inspector-egui)
 
+/// Conversions between Bevy and Egui types.
+pub mod conversions;
 /// Plugin systems for the render app.
 pub mod render_systems;
 /// Plugin systems.
@@ -62,8 +72,8 @@ pub mod egui_node;
 pub use egui;
 
 use crate::{
-    egui_node::{EguiPipeline, EGUI_SHADER_HANDLE},
-    render_systems::EguiTransforms,
+    egui_node::{EguiNode, EguiPipeline, EGUI_SHADER_HANDLE},
+    render_systems::{EguiCapturedImages, EguiDrawCalls, EguiPendingCaptures, EguiTransforms},
     systems::*,
 };
 #[cfg(all(feature = "manage_clipboard", not(target_arch = "wasm32")))]
@@ -72,33 +82,161 @@ use bevy::{
     app::{App, Plugin},
     asset::{AssetEvent, Assets, Handle},
     ecs::{
-        event::EventReader,
+        event::{EventReader, EventWriter, Events},
         query::{QueryEntityError, WorldQuery},
-        schedule::apply_system_buffers,
-        system::{ResMut, SystemParam},
+        removal_detection::RemovedComponents,
+        schedule::{apply_system_buffers, common_conditions::resource_exists, Condition},
+        system::{Res, ResMut, SystemParam},
     },
-    input::InputSystem,
+    input::{gamepad::GamepadButtonType, keyboard::KeyCode, Input, InputSystem},
     log,
+    math::{Ray, Rect, Vec2, Vec3},
     prelude::{
-        Added, Commands, Component, CoreSet, Deref, DerefMut, Entity, IntoSystemAppConfigs,
-        IntoSystemConfig, IntoSystemConfigs, Query, Resource, Shader, StartupSet, SystemSet, With,
-        Without,
+        Added, Commands, Component, CoreSchedule, CoreSet, Deref, DerefMut, DetectChanges, Entity,
+        IntoSystemAppConfig, IntoSystemAppConfigs, IntoSystemConfig, IntoSystemConfigs, Or, Query,
+        Ref, Resource, Shader, StartupSet, SystemSet, With, Without, World,
     },
     render::{
-        render_resource::SpecializedRenderPipelines, texture::Image, ExtractSchedule, RenderApp,
-        RenderSet,
+        camera::Camera, color::Color, main_graph::node::CAMERA_DRIVER,
+        render_asset::PrepareAssetSet, render_graph::RenderGraph,
+        render_resource::SpecializedRenderPipelines, renderer::RenderDevice, texture::Image,
+        ExtractSchedule, RenderApp, RenderSet,
     },
-    utils::HashMap,
-    window::{PrimaryWindow, Window},
+    transform::components::GlobalTransform,
+    utils::{Duration, HashMap, Instant},
+    window::{CursorMoved, PrimaryWindow, Window},
 };
-use std::borrow::Cow;
 #[cfg(all(feature = "manage_clipboard", not(target_arch = "wasm32")))]
 use std::cell::{RefCell, RefMut};
+use std::{any::Any, borrow::Cow, collections::VecDeque, sync::Arc};
 #[cfg(all(feature = "manage_clipboard", not(target_arch = "wasm32")))]
 use thread_local::ThreadLocal;
 
 /// Adds all Egui resources and render graph nodes.
-pub struct EguiPlugin;
+///
+/// Use [`EguiPlugin::default`] to add the plugin with the default configuration, or the builder
+/// methods to configure it:
+/// ```rust
+/// use bevy_egui::EguiPlugin;
+///
+/// let egui_plugin = EguiPlugin::default().with_scale_factor(2.0);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EguiPlugin {
+    settings: Option<EguiSettings>,
+    fallback_fonts: Vec<(String, egui::FontData)>,
+    run_manually: bool,
+    run_in_fixed_update: bool,
+    render_node_placement: EguiRenderNodePlacement,
+}
+
+impl EguiPlugin {
+    /// Sets the initial [`EguiSettings`] resource.
+    ///
+    /// If not set, the plugin initializes the resource with its default value, unless it's
+    /// already inserted.
+    #[must_use]
+    pub fn with_settings(mut self, settings: EguiSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Sets the initial [`EguiSettings::scale_factor`].
+    #[must_use]
+    pub fn with_scale_factor(mut self, scale_factor: f64) -> Self {
+        self.settings
+            .get_or_insert_with(Default::default)
+            .scale_factor = scale_factor;
+        self
+    }
+
+    /// Disables beginning and ending Egui frames automatically (`false` by default).
+    ///
+    /// In the manual mode, frames are run with [`EguiManualFrames::begin_frame`] and
+    /// [`EguiManualFrames::end_frame`], which can be called from any system that runs after
+    /// [`EguiSet::ProcessInput`] (so that the frame receives the input of the current update) and
+    /// before [`EguiSet::ProcessOutput`]. Output of frames that are ended later is processed,
+    /// and thus extracted for rendering, during the next update. If a context runs several frames
+    /// during one update, only the shapes of the last one are painted.
+    #[must_use]
+    pub fn run_manually(mut self, run_manually: bool) -> Self {
+        self.run_manually = run_manually;
+        self
+    }
+
+    /// Runs Egui frames in [`CoreSchedule::FixedUpdate`] instead of once per update (`false` by
+    /// default), which makes UI logic deterministic for networked games and replays.
+    ///
+    /// Input is still gathered every update and accumulates in [`EguiInput`] until the next fixed
+    /// step begins a frame, so if several steps run during one update, only the first one
+    /// receives the events. The time of the contexts advances by
+    /// [`bevy::time::fixed_timestep::FixedTime::period`] with each step, regardless of
+    /// [`EguiTimeSource`]. Output of all the steps is processed once per update, if no step runs,
+    /// the shapes of the last one are drawn again.
+    ///
+    /// UI systems have to be added to the same schedule:
+    /// `system.after(EguiSet::BeginFrame).before(EguiSet::ProcessOutput).in_schedule(CoreSchedule::FixedUpdate)`.
+    /// Has no effect if [`EguiPlugin::run_manually`] is set.
+    #[must_use]
+    pub fn run_in_fixed_update(mut self, run_in_fixed_update: bool) -> Self {
+        self.run_in_fixed_update = run_in_fixed_update;
+        self
+    }
+
+    /// Sets where the [`node::EGUI_PASS`] node is inserted into the render graph
+    /// ([`EguiRenderNodePlacement::AfterCameraDriver`] by default).
+    #[must_use]
+    pub fn with_render_node_placement(mut self, placement: EguiRenderNodePlacement) -> Self {
+        self.render_node_placement = placement;
+        self
+    }
+
+    /// Adds a font that is used for the glyphs missing from Egui's fonts (such as CJK characters
+    /// or emoji), see [`EguiFallbackFonts`].
+    ///
+    /// Can be called several times, fonts added earlier are tried first.
+    #[must_use]
+    pub fn with_fallback_font(mut self, name: impl Into<String>, font: egui::FontData) -> Self {
+        self.fallback_fonts.push((name.into(), font));
+        self
+    }
+}
+
+/// Where the [`node::EGUI_PASS`] node is inserted into the main render graph.
+///
+/// The node always draws straight into the window surfaces, on top of what's already there. It
+/// writes colors in the surface's color space, i.e. sRGB encoded by the surface format (or by the
+/// shader, see [`egui_node::EguiPipelineKey`]), so Egui colors are never affected by tonemapping,
+/// bloom or other camera post-processing, which are done inside the camera sub-graphs that run
+/// as part of [`CAMERA_DRIVER`]. Rendering Egui before the post-processing of a camera would mean
+/// blending sRGB UI colors into the camera's linear (possibly HDR) target, which then gets
+/// tonemapped, so UI colors wouldn't match the ones set in the style.
+///
+/// [`EguiRenderNodePlacement::After`] and [`EguiRenderNodePlacement::Before`] only accept the
+/// labels of nodes that are already in the main graph when [`EguiPlugin`] is added, an error is
+/// logged for other labels. Nodes of the camera sub-graphs (such as tonemapping or bloom) can't
+/// be targeted, and nodes added by plugins registered after [`EguiPlugin`] have to be connected
+/// with [`EguiRenderNodePlacement::Manual`] instead.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum EguiRenderNodePlacement {
+    /// Runs the node after all the cameras have been rendered, so Egui is drawn on top of them.
+    #[default]
+    AfterCameraDriver,
+    /// Runs the node after the given main graph nodes, which should include [`CAMERA_DRIVER`] if
+    /// Egui is expected to be drawn on top of the cameras.
+    After(Vec<Cow<'static, str>>),
+    /// Runs the node before the given main graph nodes, which is useful for custom nodes that
+    /// need to draw on top of Egui.
+    ///
+    /// [`CAMERA_DRIVER`] still runs before the node.
+    Before(Vec<Cow<'static, str>>),
+    /// Adds the node without any edges, so that the app can position it by calling
+    /// [`RenderGraph::add_node_edge`] itself.
+    Manual,
+    /// Doesn't add the node, for apps that draw the tessellated output of the contexts
+    /// ([`EguiRenderOutput`]) with their own renderers.
+    Disabled,
+}
 
 /// A resource for storing global UI settings.
 #[derive(Clone, Debug, PartialEq, Resource)]
@@ -116,7 +254,108 @@ pub struct EguiSettings {
     ///     }
     /// }
     /// ```
+    ///
+    /// The same can be achieved by setting [`EguiSettings::scale_factor_mode`] to
+    /// [`EguiScaleFactorMode::Fixed`].
     pub scale_factor: f64,
+    /// Whether [`EguiSettings::scale_factor`] is applied on top of the scale factors of windows,
+    /// which are set by the OS depending on the monitor DPI (the default), or replaces them.
+    pub scale_factor_mode: EguiScaleFactorMode,
+    /// How many logical points a single line of mouse wheel scrolling corresponds to (`50.0` by default).
+    ///
+    /// Is applied to [`MouseScrollUnit::Line`](bevy::input::mouse::MouseScrollUnit::Line) events
+    /// (usually sent by mouse wheels), while [`MouseScrollUnit::Pixel`](bevy::input::mouse::MouseScrollUnit::Pixel)
+    /// events (usually sent by trackpads) are converted from physical pixels to logical points.
+    pub scroll_line_height: f32,
+    /// Inverts the scrolling direction (`false` by default).
+    ///
+    /// Can be used to implement "natural" scrolling on platforms that don't apply it themselves.
+    pub invert_scroll: bool,
+    /// Smoothing of scrolling (disabled by default).
+    ///
+    /// Smoothed scroll deltas are accumulated and passed to Egui over several frames, so that
+    /// coarse mouse wheel steps turn into continuous scrolling. Smoothing is applied after
+    /// the deltas are converted to points (see [`EguiSettings::scroll_line_height`]) and
+    /// inverted. Pixel deltas are usually sent by trackpads, which already apply momentum, so
+    /// smoothing them too can make scroll areas lag or overshoot, see [`EguiScrollSmoothing`].
+    pub scroll_smoothing: EguiScrollSmoothing,
+    /// Decides which mouse wheel events are passed to Egui (all of them by default), see
+    /// [`EguiScrollPolicy`].
+    ///
+    /// The context that received the scrolling of the current frame is stored in
+    /// the [`EguiScrollCapture`] resource, so that picking and camera controllers can ignore it.
+    pub scroll_policy: EguiScrollPolicy,
+    /// Whether the cursor movements of a frame are passed to Egui as a single
+    /// [`egui::Event::PointerMoved`] with the latest position (`true` by default).
+    ///
+    /// High-frequency mice can report dozens of movements per frame, while Egui only needs
+    /// the final position. Can be disabled to pass all the positions, for example, for gesture
+    /// recognition.
+    pub coalesce_pointer_moves: bool,
+    /// Luminance of Egui's white on HDR (floating point) targets, in nits (`80.0` by default).
+    ///
+    /// Floating point targets are extended-range, where `1.0` corresponds to 80 nits (scRGB).
+    /// Egui colors are scaled so that white matches this luminance (often called "paper white"),
+    /// which keeps overlays from looking dim or blown out next to HDR content. Doesn't affect
+    /// other targets.
+    pub hdr_paper_white: f32,
+    /// Maximum size of Egui managed textures (the font atlas, etc) in pixels, is [`None`] by
+    /// default.
+    ///
+    /// Egui keeps its textures within the limit of the render device
+    /// (`max_texture_dimension_2d`, which is as low as 2048 on some WebGL2 implementations),
+    /// this setting can lower it further.
+    pub max_texture_side: Option<usize>,
+    /// How the clip rects of Egui shapes are rounded to whole pixels
+    /// ([`EguiScissorRounding::Nearest`] by default).
+    pub scissor_rounding: EguiScissorRounding,
+    /// Sampler filtering of the font atlas of all the contexts ([`EguiTextureOptions::LINEAR`]
+    /// by default).
+    ///
+    /// [`EguiTextureOptions::NEAREST`] renders text crisp and pixelated, which some prefer on
+    /// low-DPI screens or with pixel art fonts. The atlas has no mipmaps. User textures have
+    /// their own options, see [`EguiUserTextures::add_image_with_options`].
+    pub font_atlas_options: EguiTextureOptions,
+    /// Whether touches are passed to Egui as [`egui::Event::Touch`] events (`true` by default).
+    ///
+    /// Egui recognizes multi-touch gestures from them, so that two-finger pinches zoom plots and
+    /// other zoomable areas (see [`egui::InputState::zoom_delta`]). Can be disabled if the app
+    /// handles gestures itself, the first touch still emulates the pointer in either case.
+    pub touch_gestures: bool,
+    /// Whether the IME of windows is enabled while Egui has a focused text field, and its
+    /// candidate window is moved to the text cursor (`true` by default).
+    ///
+    /// Can be disabled if the app manages the IME itself, see [`EguiImeRequest`].
+    pub manage_ime: bool,
+    /// Whether typed text is only passed to a context while it wants keyboard input
+    /// (see [`egui::Context::wants_keyboard_input`]), i.e. while a text field has focus
+    /// (`false` by default).
+    ///
+    /// By default, [`egui::Event::Text`] events are sent for all the characters typed into
+    /// the focused window, like [`egui::Event::Key`] events are. Enabling this keeps the plain
+    /// keys used by the app (for example, for movement) out of Egui's input, the key events are
+    /// still sent. Can be set per platform with `cfg!`.
+    pub text_input_requires_focus: bool,
+    /// Whether [`egui::Key::Tab`] is only passed to a context while one of its widgets has
+    /// keyboard focus (see [`egui::Context::wants_keyboard_input`]), `false` by default.
+    ///
+    /// By default, Egui uses Tab for moving focus between its widgets even if none of them is
+    /// focused, which takes Tab over from the app. Enabling this leaves Tab to the app (for
+    /// example, for the app's own focus traversal) until a widget is focused with the pointer.
+    pub tab_requires_focus: bool,
+    /// Whether Egui sets the cursor icon of windows even when it doesn't use the pointer or
+    /// the cursor is grabbed (`false` by default).
+    ///
+    /// By default, the cursor is left to the app in such cases (for example, while the cursor is
+    /// grabbed for camera control).
+    pub force_cursor_icon: bool,
+    /// Is applied to every newly created Egui context before its first frame, see [`EguiSettings::with_egui_options`].
+    pub egui_options: Option<EguiOptionsCallback>,
+    /// Is invoked for every context right before its frame begins, see [`EguiSettings::with_raw_input_hook`].
+    pub raw_input_hook: Option<EguiRawInputHook>,
+    /// Replaces the fragment stage of the built-in Egui shader, can be used for applying effects
+    /// to the UI. See [`EguiCustomShader`].
+    pub custom_shader: Option<EguiCustomShader>,
     /// Will be used as a default value for hyperlink [target](https://www.w3schools.com/tags/att_a_target.asp) hints.
     /// If not specified, `_self` will be used. Only matters in a web browser.
     #[cfg(feature = "open_url")]
@@ -127,18 +366,498 @@ impl Default for EguiSettings {
     fn default() -> Self {
         Self {
             scale_factor: 1.0,
+            scale_factor_mode: EguiScaleFactorMode::default(),
+            scroll_line_height: 50.0,
+            invert_scroll: false,
+            scroll_smoothing: EguiScrollSmoothing::default(),
+            scroll_policy: EguiScrollPolicy::default(),
+            coalesce_pointer_moves: true,
+            hdr_paper_white: 80.0,
+            max_texture_side: None,
+            scissor_rounding: EguiScissorRounding::default(),
+            font_atlas_options: EguiTextureOptions::LINEAR,
+            touch_gestures: true,
+            manage_ime: true,
+            text_input_requires_focus: false,
+            tab_requires_focus: false,
+            force_cursor_icon: false,
+            egui_options: None,
+            raw_input_hook: None,
+            custom_shader: None,
             #[cfg(feature = "open_url")]
             default_open_url_target: None,
         }
     }
 }
 
+impl EguiSettings {
+    /// Returns the maximum size of Egui managed textures, taking the limits of the render device
+    /// into account, see [`EguiSettings::max_texture_side`].
+    pub fn effective_max_texture_side(
+        &self,
+        render_device: Option<&RenderDevice>,
+    ) -> Option<usize> {
+        let device_limit = render_device
+            .map(|render_device| render_device.limits().max_texture_dimension_2d as usize);
+        match (self.max_texture_side, device_limit) {
+            (Some(max_texture_side), Some(device_limit)) => {
+                Some(max_texture_side.min(device_limit))
+            }
+            (max_texture_side, device_limit) => max_texture_side.or(device_limit),
+        }
+    }
+
+    /// Returns the factor applied on top of the scale factor of a window to get Egui's
+    /// `pixels_per_point`, according to [`EguiSettings::scale_factor_mode`] and the zoom factor
+    /// of the context (see [`EguiZoomFactor`]).
+    pub fn effective_scale_factor(&self, window_size: WindowSize) -> f32 {
+        let scale_factor = self.scale_factor as f32;
+        let scale_factor = match self.scale_factor_mode {
+            EguiScaleFactorMode::FollowWindow => scale_factor,
+            EguiScaleFactorMode::Fixed => scale_factor / window_size.scale_factor,
+            EguiScaleFactorMode::ReferenceResolution(reference_size) => {
+                let content_scale = (window_size.physical_width / reference_size.x)
+                    .min(window_size.physical_height / reference_size.y);
+                if content_scale > 0.0 && content_scale.is_finite() {
+                    scale_factor * content_scale / window_size.scale_factor
+                } else {
+                    scale_factor
+                }
+            }
+        };
+        scale_factor * window_size.zoom_factor
+    }
+
+    /// Sets a callback for configuring [`egui::Options`] and [`egui::Style`] of every newly
+    /// created Egui context.
+    ///
+    /// Can be used to tune interaction settings (see [`egui::style::Interaction`]) consistently
+    /// for all the windows:
+    /// ```rust
+    /// use bevy_egui::EguiSettings;
+    ///
+    /// let egui_settings = EguiSettings::default().with_egui_options(|_options, style| {
+    ///     style.interaction.show_tooltips_only_when_still = false;
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_egui_options(
+        mut self,
+        callback: impl Fn(&mut egui::Options, &mut egui::Style) + Send + Sync + 'static,
+    ) -> Self {
+        self.egui_options = Some(EguiOptionsCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Sets a hook for modifying the assembled [`egui::RawInput`] of each context right before
+    /// its frame begins (during the [`EguiSet::BeginFrame`] system).
+    ///
+    /// The hook receives the window entity of the context and can drop or inject events,
+    /// rewrite modifiers, etc:
+    /// ```rust
+    /// use bevy_egui::{egui, EguiSettings};
+    ///
+    /// let egui_settings = EguiSettings::default().with_raw_input_hook(|_window, raw_input| {
+    ///     raw_input
+    ///         .events
+    ///         .retain(|event| !matches!(event, egui::Event::Zoom(_)));
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_raw_input_hook(
+        mut self,
+        hook: impl Fn(Entity, &mut egui::RawInput) + Send + Sync + 'static,
+    ) -> Self {
+        self.raw_input_hook = Some(EguiRawInputHook(Arc::new(hook)));
+        self
+    }
+}
+
+/// A custom fragment shader for rendering Egui, see [`EguiSettings::custom_shader`].
+///
+/// The fragment entry point receives the output of the built-in vertex stage: the linear vertex
+/// color at `@location(0)` and the texture coordinates at `@location(1)`. It can use the same
+/// bindings as the built-in shader: the transform uniform at `@group(0) @binding(0)`, and
+/// the texture and its sampler at `@group(1) @binding(0)` and `@group(1) @binding(1)`. The output
+/// is expected to be premultiplied by alpha. If the target format doesn't encode sRGB, the shader
+/// is compiled with the `GAMMA_OUTPUT` definition (see [`egui_node::EguiPipelineKey`]), and with
+/// [`EguiBlendMode::Straight`], it's compiled with the `STRAIGHT_ALPHA` definition and is expected
+/// to output colors that aren't premultiplied.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EguiCustomShader {
+    /// Shader containing the fragment entry point.
+    pub shader: Handle<Shader>,
+    /// Name of the fragment entry point.
+    pub fragment_entry_point: Cow<'static, str>,
+}
+
+/// A hook for modifying raw input of Egui contexts, see [`EguiSettings::with_raw_input_hook`].
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct EguiRawInputHook(pub Arc<dyn Fn(Entity, &mut egui::RawInput) + Send + Sync>);
+
+impl std::fmt::Debug for EguiRawInputHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EguiRawInputHook").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for EguiRawInputHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A resource holding a predicate that decides which input events are passed to Egui contexts.
+///
+/// Is consulted for every event right before a frame begins (before
+/// [`EguiSettings::raw_input_hook`]), the events it rejects are dropped. Unlike the hook, it can be
+/// inserted, replaced or removed at runtime, for example, to keep Egui from consuming arrow keys
+/// while a game menu is open:
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{egui, EguiEventFilter};
+///
+/// fn open_menu(mut commands: Commands) {
+///     commands.insert_resource(EguiEventFilter::new(|_window, event| {
+///         !matches!(
+///             event,
+///             egui::Event::Key {
+///                 key: egui::Key::ArrowUp | egui::Key::ArrowDown,
+///                 ..
+///             }
+///         )
+///     }));
+/// }
+///
+/// fn close_menu(mut commands: Commands) {
+///     commands.remove_resource::<EguiEventFilter>();
+/// }
+/// ```
+#[derive(Resource)]
+#[allow(clippy::type_complexity)]
+pub struct EguiEventFilter(pub Box<dyn Fn(Entity, &egui::Event) -> bool + Send + Sync>);
+
+impl EguiEventFilter {
+    /// Creates the filter from a predicate, which receives the window entity of the context and
+    /// returns `true` for the events that should be passed to it.
+    pub fn new(filter: impl Fn(Entity, &egui::Event) -> bool + Send + Sync + 'static) -> Self {
+        Self(Box::new(filter))
+    }
+}
+
+/// Draws a window into every context that shows the input bevy_egui passes to Egui (pointer
+/// position, modifiers, focus, scale and recent events), for diagnosing input issues.
+///
+/// The resource isn't inserted by the plugin, the overlay is drawn while it exists and
+/// [`EguiInputDebugOverlay::enabled`] is set. It shows the input after [`EguiEventFilter`] and
+/// [`EguiSettings::raw_input_hook`] are applied, i.e. exactly what Egui receives. The overlay
+/// isn't drawn if [`EguiPlugin::run_manually`] is set.
+#[derive(Resource, Clone, Debug)]
+pub struct EguiInputDebugOverlay {
+    /// Whether the overlay is drawn (`true` by default).
+    pub enabled: bool,
+    /// How many recent events are listed for each context (`20` by default). Pointer movement
+    /// isn't listed, as the pointer position is shown separately.
+    pub max_events: usize,
+    recent_events: HashMap<Entity, VecDeque<egui::Event>>,
+}
+
+impl Default for EguiInputDebugOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_events: 20,
+            recent_events: HashMap::default(),
+        }
+    }
+}
+
+/// Selects how clip rects are rounded to scissor rects, see [`EguiSettings::scissor_rounding`].
+///
+/// With fractional scale factors, the edges of clip rects fall between pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EguiScissorRounding {
+    /// Edges are rounded to the nearest pixel, which matches Egui's own renderers, but can clip
+    /// off a partially covered row or column of pixels at the edges of panels.
+    #[default]
+    Nearest,
+    /// Edges are rounded outwards, so that partially covered pixels are never clipped off.
+    /// Shapes can bleed into the neighbouring pixel instead.
+    Outward,
+}
+
+/// Selects the scroll deltas that are smoothed, see [`EguiSettings::scroll_smoothing`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EguiScrollSmoothing {
+    /// Scroll deltas are passed to Egui as they arrive.
+    #[default]
+    Disabled,
+    /// Only [`MouseScrollUnit::Line`](bevy::input::mouse::MouseScrollUnit::Line) deltas
+    /// (usually sent by mouse wheels) are smoothed.
+    Lines,
+    /// Both line and pixel deltas are smoothed, for platforms that don't smooth trackpad
+    /// scrolling themselves.
+    All,
+}
+
+/// Selects the mouse wheel events that are passed to Egui, see [`EguiSettings::scroll_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EguiScrollPolicy {
+    /// Scrolling is passed to the context under the cursor, even if it's over the 3D scene.
+    #[default]
+    Always,
+    /// Scrolling is only passed to the context under the cursor while it wants pointer input
+    /// (see [`egui::Context::wants_pointer_input`]), i.e. while the cursor is over an Egui area
+    /// or Egui is dragging something. Otherwise it's left to the app.
+    ///
+    /// The decision is based on the state of the previous frame, as the current one hasn't been
+    /// laid out yet when the input is processed.
+    WhenWantsPointer,
+}
+
+/// Defines how Egui's `pixels_per_point` is derived, see [`EguiSettings::scale_factor_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EguiScaleFactorMode {
+    /// `pixels_per_point` equals the scale factor of a window multiplied by
+    /// [`EguiSettings::scale_factor`]. Follows changes of the window scale factor live, for
+    /// example when a window is moved to another monitor.
+    #[default]
+    FollowWindow,
+    /// `pixels_per_point` equals [`EguiSettings::scale_factor`], regardless of the scale factors
+    /// of windows.
+    Fixed,
+    /// Scales the UI with the window, so that the layout designed for the reference resolution
+    /// (in physical pixels) keeps its relative size at any resolution.
+    ///
+    /// `pixels_per_point` equals the ratio of the window's physical size to the reference
+    /// resolution (the smaller one of the two axes, so that the UI always fits) multiplied by
+    /// [`EguiSettings::scale_factor`]. The scale factor of the window isn't taken into account.
+    ReferenceResolution(Vec2),
+}
+
+/// A callback for configuring newly created Egui contexts, see [`EguiSettings::with_egui_options`].
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct EguiOptionsCallback(pub Arc<dyn Fn(&mut egui::Options, &mut egui::Style) + Send + Sync>);
+
+impl EguiOptionsCallback {
+    /// Applies the callback to the context options and style.
+    pub fn apply(&self, ctx: &egui::Context) {
+        let mut style = (*ctx.style()).clone();
+        ctx.options_mut(|options| (self.0)(options, &mut style));
+        ctx.set_style(style);
+    }
+}
+
+impl std::fmt::Debug for EguiOptionsCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EguiOptionsCallback").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for EguiOptionsCallback {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Visuals shared by all Egui contexts.
+///
+/// The resource isn't inserted by the plugin. Once inserted, its visuals are applied to all
+/// the contexts whenever it changes, and to contexts created afterwards.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct EguiTheme(pub egui::Visuals);
+
+impl EguiTheme {
+    /// The default dark theme of Egui.
+    pub fn dark() -> Self {
+        Self(egui::Visuals::dark())
+    }
+
+    /// The default light theme of Egui.
+    pub fn light() -> Self {
+        Self(egui::Visuals::light())
+    }
+}
+
+impl Default for EguiTheme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// Fonts that are appended to the end of all the font families of Egui's default fonts, so that
+/// they are used for the glyphs Egui's fonts don't have (such as CJK characters or emoji).
+///
+/// The resource is inserted by [`EguiPlugin::with_fallback_font`], or it can be inserted
+/// manually, for example, once the font data is loaded. Its fonts are installed into all the
+/// contexts whenever it changes, and into contexts created afterwards, replacing fonts set with
+/// [`egui::Context::set_fonts`]. Apps that install their own fonts should add the fallback fonts
+/// to their [`egui::FontDefinitions`] instead.
+///
+/// ```rust
+/// use bevy_egui::{egui, EguiPlugin};
+///
+/// # fn load_font() -> Vec<u8> { Vec::new() }
+/// let egui_plugin =
+///     EguiPlugin::default().with_fallback_font("NotoSansCJK", egui::FontData::from_owned(load_font()));
+/// ```
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct EguiFallbackFonts(pub Vec<(String, egui::FontData)>);
+
+impl EguiFallbackFonts {
+    /// Returns Egui's default fonts with the fallback fonts appended to all the families.
+    #[must_use]
+    pub fn font_definitions(&self) -> egui::FontDefinitions {
+        let mut fonts = egui::FontDefinitions::default();
+        for (name, font) in &self.0 {
+            fonts.font_data.insert(name.clone(), font.clone());
+            for family in fonts.families.values_mut() {
+                family.push(name.clone());
+            }
+        }
+        fonts
+    }
+}
+
 /// Is used for storing Egui context input..
 ///
 /// It gets reset during the [`EguiSet::ProcessInput`] system.
 #[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
 pub struct EguiInput(pub egui::RawInput);
 
+/// Selects the clock that drives the time of an Egui context, which is used for animations,
+/// tooltip delays, etc.
+///
+/// Every frame, [`egui::RawInput::predicted_dt`] is set to the delta of the selected clock, so
+/// that animations keep their speed when the frame rate fluctuates. Contexts without this component use [`EguiTimeSource::Real`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EguiTimeSource {
+    /// Wall-clock time, which isn't affected by pausing or scaling Bevy's [`Time`](bevy::time::Time).
+    #[default]
+    Real,
+    /// Bevy's virtual time, which honors [`Time::relative_speed`](bevy::time::Time::relative_speed)
+    /// and [`Time::pause`](bevy::time::Time::pause).
+    Virtual,
+}
+
+/// An Egui navigation action, which gamepad input is translated to.
+///
+/// Actions are sent to Egui as key presses, see [`EguiGamepadBindings`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EguiNavigationAction {
+    /// Moves focus to the next widget (`Tab`).
+    Next,
+    /// Moves focus to the previous widget (`Shift+Tab`).
+    Previous,
+    /// `ArrowUp`, changes values of focused widgets, such as sliders.
+    Up,
+    /// `ArrowDown`, changes values of focused widgets, such as sliders.
+    Down,
+    /// `ArrowLeft`, changes values of focused widgets, such as sliders.
+    Left,
+    /// `ArrowRight`, changes values of focused widgets, such as sliders.
+    Right,
+    /// Clicks the focused widget (`Enter`).
+    Activate,
+    /// Removes focus from the focused widget (`Escape`).
+    Cancel,
+}
+
+impl EguiNavigationAction {
+    fn key(self) -> (egui::Key, egui::Modifiers) {
+        match self {
+            Self::Next => (egui::Key::Tab, egui::Modifiers::NONE),
+            Self::Previous => (egui::Key::Tab, egui::Modifiers::SHIFT),
+            Self::Up => (egui::Key::ArrowUp, egui::Modifiers::NONE),
+            Self::Down => (egui::Key::ArrowDown, egui::Modifiers::NONE),
+            Self::Left => (egui::Key::ArrowLeft, egui::Modifiers::NONE),
+            Self::Right => (egui::Key::ArrowRight, egui::Modifiers::NONE),
+            Self::Activate => (egui::Key::Enter, egui::Modifiers::NONE),
+            Self::Cancel => (egui::Key::Escape, egui::Modifiers::NONE),
+        }
+    }
+}
+
+/// Maps gamepad input to Egui navigation, so that UI can be used with a controller.
+///
+/// Gamepad navigation is disabled unless this resource is inserted:
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::EguiGamepadBindings;
+///
+/// App::new().init_resource::<EguiGamepadBindings>();
+/// ```
+/// Input of all the connected gamepads is passed to the context that receives keyboard input,
+/// see [`EguiFocusedWindow`].
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct EguiGamepadBindings {
+    /// Actions triggered by gamepad buttons.
+    pub buttons: HashMap<GamepadButtonType, EguiNavigationAction>,
+    /// How far the left stick needs to be tilted to trigger [`EguiNavigationAction::Previous`]
+    /// (up), [`EguiNavigationAction::Next`] (down), [`EguiNavigationAction::Left`] or
+    /// [`EguiNavigationAction::Right`]. The stick is ignored if [`None`].
+    pub stick_threshold: Option<f32>,
+}
+
+impl Default for EguiGamepadBindings {
+    fn default() -> Self {
+        Self {
+            buttons: [
+                (GamepadButtonType::DPadUp, EguiNavigationAction::Previous),
+                (GamepadButtonType::DPadDown, EguiNavigationAction::Next),
+                (GamepadButtonType::DPadLeft, EguiNavigationAction::Left),
+                (GamepadButtonType::DPadRight, EguiNavigationAction::Right),
+                (GamepadButtonType::South, EguiNavigationAction::Activate),
+                (GamepadButtonType::East, EguiNavigationAction::Cancel),
+            ]
+            .into_iter()
+            .collect(),
+            stick_threshold: Some(0.5),
+        }
+    }
+}
+
+/// Controls which input is passed to an Egui context.
+///
+/// Disabled input isn't fed to Egui, so the context doesn't react to it and doesn't report that
+/// it wants it (see [`egui::Context::wants_pointer_input`]), while it's still rendered. Is
+/// inserted with both kinds of input enabled when a context is initialized, use
+/// [`EguiContexts::set_input_enabled`] to toggle it.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EguiInputEnabled {
+    /// Whether pointer input (moves, clicks and scrolling) is passed to Egui.
+    pub pointer: bool,
+    /// Whether keyboard input (keys and text) is passed to Egui.
+    pub keyboard: bool,
+}
+
+impl Default for EguiInputEnabled {
+    fn default() -> Self {
+        Self {
+            pointer: true,
+            keyboard: true,
+        }
+    }
+}
+
+/// Disables an Egui context entirely when inserted into its window entity.
+///
+/// A disabled context doesn't receive input, doesn't run frames and isn't rendered, while it keeps
+/// its state, so that it can be re-enabled by removing the component. Systems that draw into the
+/// context should skip it as well, as its frames aren't ended.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct EguiDisabled;
+
+/// Stops an Egui context from being rendered when inserted into its window entity.
+///
+/// Unlike [`EguiDisabled`], the context keeps receiving input and running frames, so that its
+/// state persists (which is useful for hiding the UI in cutscenes or screenshots). Is toggled by
+/// [`EguiContexts::set_render_enabled`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct EguiRenderDisabled;
+
 /// A resource for accessing clipboard.
 ///
 /// The resource is available only if `manage_clipboard` feature is enabled.
@@ -196,7 +915,7 @@ impl EguiClipboard {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    fn get(&self) -> Option<RefMut<Clipboard>> {
+    fn get(&self) -> Option<RefMut<'_, Clipboard>> {
         self.clipboard
             .get_or(|| {
                 Clipboard::new()
@@ -212,25 +931,82 @@ impl EguiClipboard {
 }
 
 /// Is used for storing Egui shapes and textures delta.
+///
+/// Can be consumed by custom renderers (see [`EguiRenderNodePlacement::Disabled`]).
 #[derive(Component, Clone, Default, Debug, Resource)]
 pub struct EguiRenderOutput {
     /// Pairs of rectangles and paint commands.
     ///
     /// The field gets populated during the [`EguiSet::ProcessOutput`] system (belonging to [`CoreSet::PostUpdate`]) and reset during `EguiNode::update`.
+    ///
+    /// Vertex positions and clip rectangles are in Egui points, relative to the context's screen
+    /// rect (or its [`EguiViewport`]). Multiply them by [`egui::Context::pixels_per_point`] to get
+    /// physical pixels.
     pub paint_jobs: Vec<egui::ClippedPrimitive>,
 
     /// The change in egui textures since last frame.
+    ///
+    /// Is drained by [`update_egui_textures_system`] (created textures) and at the end of
+    /// the frame (freed textures), so custom renderers should read it between
+    /// [`EguiSet::ProcessOutput`] and [`update_egui_textures_system`].
     pub textures_delta: egui::TexturesDelta,
 }
 
-/// Is used for storing Egui output.
+/// Is used for storing the output of ended Egui frames until it's processed.
+///
+/// The field gets populated when a frame ends (see [`EguiSet::ProcessOutput`] and
+/// [`EguiManualFrames::end_frame`]) and taken by the [`process_output_system`].
 #[derive(Component, Clone, Default)]
+pub struct EguiFullOutput(pub Option<egui::FullOutput>);
+
+/// Is used for storing Egui output.
+#[derive(Component, Clone)]
 pub struct EguiOutput {
     /// The field gets updated during the [`EguiSet::ProcessOutput`] system (belonging to [`CoreSet::PostUpdate`]).
     pub platform_output: egui::PlatformOutput,
+    /// The rect in Egui points that panels and windows took during the last completed frame
+    /// (see [`egui::Context::used_rect`]), is updated together with `platform_output`.
+    /// Is [`egui::Rect::NOTHING`] if nothing was drawn.
+    pub used_rect: egui::Rect,
+}
+
+impl Default for EguiOutput {
+    fn default() -> Self {
+        Self {
+            platform_output: Default::default(),
+            used_rect: egui::Rect::NOTHING,
+        }
+    }
 }
 
 /// A component for storing `bevy_egui` context.
+///
+/// Contexts are created automatically for every window. To create a context that isn't attached
+/// to a window (for example, in a headless app), insert this component together with
+/// a [`WindowSize`] to any entity, the rest of the context components will be added by
+/// [`EguiSet::InitContexts`]. The context can then be accessed with
+/// [`EguiContexts::ctx_for_window_mut`], passing the entity.
+///
+/// Inserting this component into a [`Camera`] entity that renders into an image
+/// ([`bevy::render::camera::RenderTarget::Image`]) draws the context on top of the image after
+/// the camera is rendered. The size and the scale factor of such contexts follow the image,
+/// so they don't need a [`WindowSize`]. For compositing the UI in 3D, clear the image to
+/// transparent with the camera's clear color (for example, a `Camera2d` with
+/// `ClearColorConfig::Custom(Color::NONE)`). Colors are then stored premultiplied by alpha in
+/// either [`EguiBlendMode`], so the image should be blended with
+/// `AlphaMode::Premultiplied`, see the `render_egui_to_texture` example.
+///
+/// Cameras that render into windows can have contexts as well, for example, for per-player UI
+/// of split-screen games. If the camera has a viewport, the context is restricted to it (see
+/// [`EguiViewport`]). Such contexts are drawn into the window together with the window's own
+/// context, in the order set by [`EguiRenderOrder`], and the pointer is passed to the topmost
+/// context under it. Keyboard input is still passed only to the window's own context.
+///
+/// Every context has its own [`egui::Memory`], so the same UI code can run for several windows
+/// without salting its ids: window positions, focus, collapsed states and the like are
+/// independent. Cloning the component shares the underlying context instead (see
+/// [`egui::Context`]), use [`EguiContexts::memory_snapshot`] and [`EguiContexts::load_memory`]
+/// for copying the state between contexts.
 #[derive(Clone, Component, Default)]
 pub struct EguiContext(egui::Context);
 
@@ -268,6 +1044,10 @@ impl EguiContext {
 #[derive(SystemParam)]
 /// A helper SystemParam that provides a way to get `[EguiContext]` with less boilerplate and
 /// combines a proxy interface to the [`EguiUserTextures`] resource.
+///
+/// It only borrows the contexts and [`EguiUserTextures`], so systems can use it together with
+/// the other bevy_egui resources, such as `ResMut<EguiSettings>`. Position conversions and
+/// manually run frames are provided by [`EguiCoordinates`] and [`EguiManualFrames`].
 pub struct EguiContexts<'w, 's> {
     q: Query<
         'w,
@@ -277,9 +1057,9 @@ pub struct EguiContexts<'w, 's> {
             &'static mut EguiContext,
             Option<&'static PrimaryWindow>,
         ),
-        With<Window>,
     >,
     user_textures: ResMut<'w, EguiUserTextures>,
+    commands: Commands<'w, 's>,
 }
 
 impl<'w, 's> EguiContexts<'w, 's> {
@@ -332,6 +1112,171 @@ impl<'w, 's> EguiContexts<'w, 's> {
             .map(|arr| arr.map(|(_window_entity, ctx, _primary_window)| ctx.into_inner().get_mut()))
     }
 
+    /// Iterates over Egui contexts of all the windows, yielding them together with their window entities.
+    ///
+    /// See [`EguiContexts::iter_contexts`] for the immutable variant (requires the `immutable_ctx` feature).
+    pub fn iter_contexts_mut(&mut self) -> impl Iterator<Item = (Entity, &mut egui::Context)> {
+        self.q
+            .iter_mut()
+            .map(|(window_entity, ctx, _primary_window)| {
+                (window_entity, ctx.into_inner().get_mut())
+            })
+    }
+
+    /// Whether a position in Egui points is over any Egui area (window, panel, etc) of a window's
+    /// context.
+    ///
+    /// Unlike [`egui::Context::is_pointer_over_area`], it can be used for testing arbitrary
+    /// positions, for example projected positions of objects in the world, see
+    /// [`EguiCoordinates`] for converting them. Returns `false` if the context doesn't exist.
+    ///
+    /// Egui doesn't have a modal layer order yet, a modal dialog can block the game underneath by
+    /// drawing a backdrop [`egui::Area`] that covers the screen in [`egui::Order::Foreground`]
+    /// (and dims it) before the dialog. The backdrop counts as an area, so both this method and
+    /// [`egui::Context::wants_pointer_input`] return `true` for any position while it's shown.
+    pub fn is_pointer_over_area(&mut self, window: Entity, pos: egui::Pos2) -> bool {
+        let Ok((_window, ctx, _primary_window)) = self.q.get_mut(window) else {
+            return false;
+        };
+        let ctx = ctx.into_inner().get_mut();
+        // Mirrors `egui::Context::is_pointer_over_area`: panels are in the background layer,
+        // and the space that isn't taken by them doesn't count.
+        match ctx.layer_id_at(pos) {
+            Some(layer_id) if layer_id.order == egui::Order::Background => {
+                !ctx.available_rect().contains(pos)
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Removes the Egui context of a window (or a windowless context), its managed textures are
+    /// freed at the end of the frame. Returns `false` if the context doesn't exist.
+    ///
+    /// Egui isn't set up for the window again, unless [`EguiContext`] is inserted manually.
+    /// Contexts of closed windows are freed automatically.
+    pub fn remove_context(&mut self, window: Entity) -> bool {
+        if !self.q.contains(window) {
+            return false;
+        }
+        self.commands.entity(window).remove::<(
+            EguiContext,
+            EguiMousePosition,
+            EguiRenderOutput,
+            EguiInput,
+            EguiOutput,
+            EguiFullOutput,
+            WindowSize,
+            EguiInputEnabled,
+            EguiZoomFactor,
+        )>();
+        true
+    }
+
+    /// Releases all the pointer buttons held in the context of a window and makes the pointer
+    /// leave it, so that Egui drops any drag state.
+    ///
+    /// Is useful when the cursor gets grabbed (for example, for camera control), as the window
+    /// stops receiving cursor movements and button releases. The events are passed to the next
+    /// frame of the context. Does nothing if the context isn't initialized.
+    pub fn release_pointer(&mut self, window: Entity) {
+        let Ok((_window, ctx, _primary_window)) = self.q.get_mut(window) else {
+            return;
+        };
+        let (pos, pressed_buttons) = ctx.into_inner().get_mut().input(|input| {
+            let pressed_buttons = [
+                egui::PointerButton::Primary,
+                egui::PointerButton::Secondary,
+                egui::PointerButton::Middle,
+                egui::PointerButton::Extra1,
+                egui::PointerButton::Extra2,
+            ]
+            .into_iter()
+            .filter(|button| input.pointer.button_down(*button))
+            .collect::<Vec<_>>();
+            (
+                input.pointer.interact_pos().unwrap_or_default(),
+                pressed_buttons,
+            )
+        });
+        let mut events = pressed_buttons
+            .into_iter()
+            .map(|button| egui::Event::PointerButton {
+                pos,
+                button,
+                pressed: false,
+                modifiers: egui::Modifiers::default(),
+            })
+            .collect::<Vec<_>>();
+        events.push(egui::Event::PointerGone);
+        self.commands.add(move |world: &mut World| {
+            if let Some(mut egui_input) = world.get_mut::<EguiInput>(window) {
+                egui_input.events.extend(events);
+            }
+        });
+    }
+
+    /// Enables or disables passing both pointer and keyboard input to the context of a window.
+    ///
+    /// The change is applied when the system's commands are applied. Does nothing if the context
+    /// isn't initialized. See [`EguiInputEnabled`].
+    pub fn set_input_enabled(&mut self, window: Entity, enabled: bool) {
+        self.update_input_enabled(window, move |input_enabled| {
+            *input_enabled = EguiInputEnabled {
+                pointer: enabled,
+                keyboard: enabled,
+            };
+        });
+    }
+
+    /// Enables or disables passing pointer input to the context of a window.
+    ///
+    /// The change is applied when the system's commands are applied. Does nothing if the context
+    /// isn't initialized. See [`EguiInputEnabled`].
+    pub fn set_pointer_input_enabled(&mut self, window: Entity, enabled: bool) {
+        self.update_input_enabled(window, move |input_enabled| {
+            input_enabled.pointer = enabled;
+        });
+    }
+
+    /// Enables or disables passing keyboard input to the context of a window.
+    ///
+    /// The change is applied when the system's commands are applied. Does nothing if the context
+    /// isn't initialized. See [`EguiInputEnabled`].
+    pub fn set_keyboard_input_enabled(&mut self, window: Entity, enabled: bool) {
+        self.update_input_enabled(window, move |input_enabled| {
+            input_enabled.keyboard = enabled;
+        });
+    }
+
+    fn update_input_enabled(
+        &mut self,
+        window: Entity,
+        update: impl FnOnce(&mut EguiInputEnabled) + Send + 'static,
+    ) {
+        self.commands.add(move |world: &mut World| {
+            if let Some(mut input_enabled) = world.get_mut::<EguiInputEnabled>(window) {
+                update(&mut input_enabled);
+            }
+        });
+    }
+
+    /// Enables or disables rendering of the context of a window, see [`EguiRenderDisabled`].
+    ///
+    /// The change is applied when the system's commands are applied. It affects the current frame
+    /// if the method is called before [`CoreSet::Last`], so that the commands are applied before
+    /// the contexts are extracted for rendering. Does nothing if the context isn't initialized.
+    pub fn set_render_enabled(&mut self, window: Entity, enabled: bool) {
+        if !self.q.contains(window) {
+            return;
+        }
+        if enabled {
+            self.commands.entity(window).remove::<EguiRenderDisabled>();
+        } else {
+            self.commands.entity(window).insert(EguiRenderDisabled);
+        }
+    }
+
     /// Egui context of the primary window.
     ///
     /// Even though the mutable borrow isn't necessary, as the context is wrapped into `RwLock`,
@@ -396,18 +1341,141 @@ impl<'w, 's> EguiContexts<'w, 's> {
             })
     }
 
-    /// Can accept either a strong or a weak handle.
+    /// Iterates over Egui contexts of all the windows, yielding them together with their window entities.
     ///
-    /// You may want to pass a weak handle if you control removing texture assets in your
-    /// application manually and you don't want to bother with cleaning up textures in Egui.
+    /// Even though the mutable borrow isn't necessary, as the context is wrapped into `RwLock`,
+    /// using the immutable getter is gated with the `immutable_ctx` feature. Using the immutable
+    /// borrow is discouraged as it may cause unpredictable blocking in UI systems.
     ///
-    /// You'll want to pass a strong handle if a texture is used only in Egui and there are no
-    /// handle copies stored anywhere else.
-    pub fn add_image(&mut self, image: Handle<Image>) -> egui::TextureId {
-        self.user_textures.add_image(image)
+    /// When the context is queried with `&mut EguiContext`, the Bevy scheduler is able to make
+    /// sure that the context isn't accessed concurrently and can perform other useful work
+    /// instead of busy-waiting.
+    #[cfg(feature = "immutable_ctx")]
+    pub fn iter_contexts(&self) -> impl Iterator<Item = (Entity, &egui::Context)> {
+        self.q
+            .iter()
+            .map(|(window_entity, ctx, _primary_window)| (window_entity, ctx.get()))
     }
 
-    /// Removes the image handle and an Egui texture id associated with it.
+    /// Returns the latest pointer position fed to the window's context, in Egui logical points
+    /// (i.e. with the window and [`EguiSettings::scale_factor`] already taken into account).
+    ///
+    /// Returns [`None`] if the context doesn't exist or the pointer isn't over the window.
+    #[must_use]
+    pub fn pointer_latest_pos(&mut self, window: Entity) -> Option<egui::Pos2> {
+        self.try_ctx_for_window_mut(window)?
+            .input(|input| input.pointer.hover_pos())
+    }
+
+    /// Returns the id of the widget that has keyboard focus in the window's context.
+    ///
+    /// Returns [`None`] if the context doesn't exist or no widget is focused.
+    #[must_use]
+    pub fn focused_widget(&mut self, window: Entity) -> Option<egui::Id> {
+        self.try_ctx_for_window_mut(window)?
+            .memory(|memory| memory.focus())
+    }
+
+    /// Returns the layer (an [`egui::Window`] or an [`egui::Area`]) under the pointer in the
+    /// window's context, which can be used to show help for the hovered part of the UI outside
+    /// of Egui. The id of the layer of a window is its [`egui::Window::id`] (the hash of its title
+    /// by default). Panels and the rest of the screen are [`egui::LayerId::background`].
+    ///
+    /// Egui doesn't keep track of the hovered widget itself, widgets can be checked with
+    /// [`egui::Response::hovered`]. Layers are hit-tested against their areas from the previous
+    /// frame. Returns [`None`] if the context doesn't exist or the pointer isn't over it.
+    #[must_use]
+    pub fn hovered_layer(&mut self, window: Entity) -> Option<egui::LayerId> {
+        let ctx = self.try_ctx_for_window_mut(window)?;
+        let pointer_pos = ctx.pointer_hover_pos()?;
+        ctx.layer_id_at(pointer_pos)
+    }
+
+    /// Moves keyboard focus to a widget of the window's context, which takes effect during
+    /// the next frame (see [`egui::Memory::request_focus`]).
+    ///
+    /// Does nothing if the context doesn't exist.
+    pub fn request_focus(&mut self, window: Entity, id: egui::Id) {
+        if let Some(ctx) = self.try_ctx_for_window_mut(window) {
+            ctx.memory_mut(|memory| memory.request_focus(id));
+        }
+    }
+
+    /// Returns a copy of the memory of the window's context (window positions, collapsed states,
+    /// etc), which can be saved to restore the UI layout on the next run with
+    /// [`EguiContexts::load_memory`].
+    ///
+    /// [`egui::Memory`] is serializable when Egui's `persistence` feature is enabled (add `egui`
+    /// with the feature to the app's dependencies), for example, with `ron`, as Egui's own
+    /// integrations do. Returns [`None`] if the context doesn't exist.
+    #[must_use]
+    pub fn memory_snapshot(&mut self, window: Entity) -> Option<egui::Memory> {
+        Some(
+            self.try_ctx_for_window_mut(window)?
+                .memory(|memory| memory.clone()),
+        )
+    }
+
+    /// Replaces the memory of the window's context, see [`EguiContexts::memory_snapshot`].
+    ///
+    /// Memory saved by an incompatible version of the app should be discarded if it fails to
+    /// deserialize. Does nothing if the context doesn't exist.
+    pub fn load_memory(&mut self, window: Entity, memory: egui::Memory) {
+        if let Some(ctx) = self.try_ctx_for_window_mut(window) {
+            ctx.memory_mut(|current| *current = memory);
+        }
+    }
+
+    /// Can accept either a strong or a weak handle.
+    ///
+    /// You may want to pass a weak handle if you control removing texture assets in your
+    /// application manually and you don't want to bother with cleaning up textures in Egui.
+    ///
+    /// You'll want to pass a strong handle if a texture is used only in Egui and there are no
+    /// handle copies stored anywhere else.
+    pub fn add_image(&mut self, image: Handle<Image>) -> egui::TextureId {
+        self.user_textures.add_image(image)
+    }
+
+    /// Adds an image (or reuses the texture id if it's already added) and returns its texture id
+    /// together with the size of the image in pixels, which can be passed to
+    /// [`egui::Ui::image`] directly.
+    ///
+    /// The image assets are passed explicitly, so that systems can use this method together with
+    /// `ResMut<Assets<Image>>`. Returns [`None`] if the image isn't loaded yet.
+    pub fn image_for(
+        &mut self,
+        image: &Handle<Image>,
+        images: &Assets<Image>,
+    ) -> Option<(egui::TextureId, egui::Vec2)> {
+        let texture_id = self.user_textures.add_image(image.clone());
+        let size = images.get(image)?.size();
+        Some((texture_id, egui::vec2(size.x, size.y)))
+    }
+
+    /// Adds an image that is sampled with the given filtering, see
+    /// [`EguiUserTextures::add_image_with_options`].
+    pub fn add_image_with_options(
+        &mut self,
+        image: Handle<Image>,
+        options: impl Into<EguiTextureOptions>,
+    ) -> egui::TextureId {
+        self.user_textures.add_image_with_options(image, options)
+    }
+
+    /// Adds a region of an image (a sprite of a sprite sheet, for example), see
+    /// [`EguiUserTextures::add_image_region`].
+    pub fn add_image_region(
+        &mut self,
+        image: Handle<Image>,
+        region: Rect,
+        image_size: Vec2,
+    ) -> (egui::TextureId, egui::Rect) {
+        self.user_textures
+            .add_image_region(image, region, image_size)
+    }
+
+    /// Removes the image handle and an Egui texture id associated with it.
     #[track_caller]
     pub fn remove_image(&mut self, image: &Handle<Image>) -> Option<egui::TextureId> {
         self.user_textures.remove_image(image)
@@ -421,14 +1489,361 @@ impl<'w, 's> EguiContexts<'w, 's> {
     }
 }
 
+/// A helper SystemParam for converting positions between windows, the world and Egui contexts.
+///
+/// Reads [`EguiSettings`] (which affects the scale of the contexts), so it can't be used
+/// together with `ResMut<EguiSettings>` in one system.
+#[derive(SystemParam)]
+pub struct EguiCoordinates<'w, 's> {
+    window_sizes: Query<'w, 's, (&'static WindowSize, Option<&'static EguiViewport>)>,
+    outputs: Query<'w, 's, &'static EguiOutput>,
+    settings: Res<'w, EguiSettings>,
+}
+
+impl<'w, 's> EguiCoordinates<'w, 's> {
+    /// Returns the rect that panels and windows of the window's context took during the last
+    /// completed frame, which can be used to keep gameplay elements from being covered by the UI.
+    ///
+    /// The rect is in logical window pixels, with the origin at the bottom left corner of the
+    /// window, same as [`Window::cursor_position`]. Egui doesn't expose the rects of individual
+    /// widgets, [`EguiContexts::is_pointer_over_area`] can be used for hit-testing positions
+    /// against the windows and areas. Returns [`None`] if the context doesn't exist or nothing
+    /// was drawn.
+    #[must_use]
+    pub fn used_rect(&self, window: Entity) -> Option<Rect> {
+        let used_rect = self.outputs.get(window).ok()?.used_rect;
+        if !used_rect.is_positive() {
+            return None;
+        }
+        Some(Rect::from_corners(
+            self.egui_to_window_pos(window, used_rect.min)?,
+            self.egui_to_window_pos(window, used_rect.max)?,
+        ))
+    }
+
+    /// Converts a window position to a position in Egui points, taking [`EguiSettings`] and
+    /// [`EguiViewport`] into account.
+    ///
+    /// The window position is in logical pixels, with the origin at the bottom left corner of
+    /// the window, same as [`Window::cursor_position`]. Returns [`None`] if the context doesn't
+    /// exist.
+    #[must_use]
+    pub fn window_to_egui_pos(&self, window: Entity, pos: Vec2) -> Option<egui::Pos2> {
+        let (window_size, viewport) = self.window_sizes.get(window).ok()?;
+        Some(window_to_egui_pos(
+            &self.settings,
+            *window_size,
+            viewport,
+            pos,
+        ))
+    }
+
+    /// The inverse of [`EguiCoordinates::window_to_egui_pos`].
+    #[must_use]
+    pub fn egui_to_window_pos(&self, window: Entity, pos: egui::Pos2) -> Option<Vec2> {
+        let (window_size, viewport) = self.window_sizes.get(window).ok()?;
+        Some(egui_to_window_pos(
+            &self.settings,
+            *window_size,
+            viewport,
+            pos,
+        ))
+    }
+
+    /// Projects a world position to a position in Egui points of a window that's rendered
+    /// by the camera, which is useful for anchoring Egui areas to objects in the world.
+    ///
+    /// Returns [`None`] if the context doesn't exist, or the position is outside of
+    /// the camera's view frustum.
+    #[must_use]
+    pub fn world_to_egui_pos(
+        &self,
+        window: Entity,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        world_pos: Vec3,
+    ) -> Option<egui::Pos2> {
+        let (window_size, _viewport) = self.window_sizes.get(window).ok()?;
+        // Viewport positions have the origin at the bottom left corner of the camera viewport.
+        let viewport_pos = camera.world_to_viewport(camera_transform, world_pos)?;
+        let (viewport_min, viewport_max) = camera.logical_viewport_rect()?;
+        let window_pos = Vec2::new(
+            viewport_min.x + viewport_pos.x,
+            window_size.height() - viewport_max.y + viewport_pos.y,
+        );
+        self.window_to_egui_pos(window, window_pos)
+    }
+
+    /// Returns a ray that goes from the camera through a position in Egui points of a window
+    /// that's rendered by the camera, the inverse of [`EguiCoordinates::world_to_egui_pos`].
+    ///
+    /// Returns [`None`] if the context doesn't exist, or the camera isn't ready yet.
+    #[must_use]
+    pub fn egui_pos_to_world_ray(
+        &self,
+        window: Entity,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        pos: egui::Pos2,
+    ) -> Option<Ray> {
+        let (window_size, _viewport) = self.window_sizes.get(window).ok()?;
+        let window_pos = self.egui_to_window_pos(window, pos)?;
+        let (viewport_min, viewport_max) = camera.logical_viewport_rect()?;
+        let viewport_pos = Vec2::new(
+            window_pos.x - viewport_min.x,
+            window_pos.y - (window_size.height() - viewport_max.y),
+        );
+        camera.viewport_to_world(camera_transform, viewport_pos)
+    }
+}
+
+/// A helper SystemParam for running Egui frames manually, see [`EguiPlugin::run_manually`].
+///
+/// It borrows the contexts mutably, so the UI of a manually run frame is drawn into the context
+/// returned by [`EguiManualFrames::begin_frame`] rather than via [`EguiContexts`] in the same
+/// system.
+///
+/// Running frames manually doesn't make it possible to paint outside the Egui schedule (for
+/// example, to show a progress spinner from a system that blocks the update): window surfaces are
+/// owned by the render app, which extracts the output and presents it only after the main app
+/// update has finished. Long-running work should be moved to a task (such as
+/// [`bevy::tasks::AsyncComputeTaskPool`]) instead, so that frames keep being rendered.
+#[derive(SystemParam)]
+pub struct EguiManualFrames<'w, 's> {
+    contexts: Query<
+        'w,
+        's,
+        (
+            &'static mut EguiContext,
+            &'static mut EguiInput,
+            &'static mut EguiFullOutput,
+        ),
+    >,
+    settings: Res<'w, EguiSettings>,
+    frame_stats: ResMut<'w, EguiFrameStats>,
+}
+
+impl<'w, 's> EguiManualFrames<'w, 's> {
+    /// Begins a frame of the context of a window, passing it the input collected since
+    /// the previous frame, and returns the context for drawing the UI.
+    ///
+    /// Is meant to be called between [`EguiSet::ProcessInput`] and [`EguiSet::ProcessOutput`],
+    /// the frame isn't painted before the update ends, see [`EguiManualFrames`].
+    #[track_caller]
+    pub fn begin_frame(&mut self, window: Entity) -> &mut egui::Context {
+        let Ok((ctx, mut egui_input, _full_output)) = self.contexts.get_mut(window) else {
+            panic!("`EguiManualFrames::begin_frame` was called for an uninitialized context (window {window:?}), make sure your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)");
+        };
+        let mut raw_input = egui_input.take();
+        if let Some(raw_input_hook) = &self.settings.raw_input_hook {
+            (raw_input_hook.0)(window, &mut raw_input);
+        }
+        self.frame_stats.begin_manual_frame();
+        let ctx = ctx.into_inner().get_mut();
+        ctx.begin_frame(raw_input);
+        ctx
+    }
+
+    /// Ends a frame of the context of a window, its output is processed during
+    /// [`EguiSet::ProcessOutput`].
+    ///
+    /// The output isn't painted immediately, it's extracted by the render app after the update,
+    /// see [`EguiManualFrames`].
+    #[track_caller]
+    pub fn end_frame(&mut self, window: Entity) {
+        let Ok((mut ctx, _egui_input, mut full_output)) = self.contexts.get_mut(window) else {
+            panic!("`EguiManualFrames::end_frame` was called for an uninitialized context (window {window:?}), make sure your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)");
+        };
+        let output = ctx.get_mut().end_frame();
+        match &mut full_output.0 {
+            Some(full_output) => full_output.append(output),
+            None => full_output.0 = Some(output),
+        }
+    }
+}
+
+/// A read-only variant of [`EguiContexts`], which queries the contexts immutably.
+///
+/// Several systems that use it can run in parallel (for example, systems that only check
+/// [`egui::Context::wants_pointer_input`]), while the access to each context is still
+/// synchronized by its lock, see [`EguiContext::get`] for the caveats. Prefer [`EguiContexts`]
+/// in systems that draw the UI.
+#[cfg(feature = "immutable_ctx")]
+#[derive(SystemParam)]
+pub struct EguiContextsRead<'w, 's> {
+    q: Query<'w, 's, (Entity, &'static EguiContext, Option<&'static PrimaryWindow>)>,
+}
+
+#[cfg(feature = "immutable_ctx")]
+impl<'w, 's> EguiContextsRead<'w, 's> {
+    /// Egui context of the primary window.
+    #[must_use]
+    pub fn ctx(&self) -> &egui::Context {
+        self.try_ctx()
+            .expect("`EguiContextsRead::ctx` was called for an uninitialized context (primary window), make sure your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)")
+    }
+
+    /// Fallible variant of [`EguiContextsRead::ctx`].
+    #[must_use]
+    pub fn try_ctx(&self) -> Option<&egui::Context> {
+        self.q
+            .iter()
+            .find_map(|(_window_entity, ctx, primary_window)| {
+                primary_window.is_some().then(|| ctx.get())
+            })
+    }
+
+    /// Egui context for a specific window.
+    #[must_use]
+    pub fn ctx_for_window(&self, window: Entity) -> &egui::Context {
+        self.try_ctx_for_window(window)
+            .unwrap_or_else(|| panic!("`EguiContextsRead::ctx_for_window` was called for an uninitialized context (window {window:?}), make sure your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)"))
+    }
+
+    /// Fallible variant of [`EguiContextsRead::ctx_for_window`].
+    #[must_use]
+    pub fn try_ctx_for_window(&self, window: Entity) -> Option<&egui::Context> {
+        self.q
+            .get(window)
+            .ok()
+            .map(|(_window_entity, ctx, _primary_window)| ctx.get())
+    }
+
+    /// Iterates over Egui contexts of all the windows, yielding them together with their window entities.
+    pub fn iter_contexts(&self) -> impl Iterator<Item = (Entity, &egui::Context)> {
+        self.q
+            .iter()
+            .map(|(window_entity, ctx, _primary_window)| (window_entity, ctx.get()))
+    }
+}
+
 /// A resource for storing `bevy_egui` mouse position.
 #[derive(Resource, Component, Default, Deref, DerefMut)]
 pub struct EguiMousePosition(pub Option<(Entity, egui::Vec2)>);
 
+/// A resource that stores the window whose context receives keyboard and gamepad input, is
+/// [`None`] if no window is focused.
+///
+/// Is updated in [`EguiSet::ProcessInput`] when windows gain or lose focus, taking
+/// [`EguiFocusPriority`] into account. It can also be set by the app, for example, to pass
+/// keyboard and gamepad navigation to a context that isn't attached to a window.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, DerefMut, PartialEq, Eq)]
+pub struct EguiFocusedWindow(pub Option<Entity>);
+
+/// A resource that stores the context that received the mouse wheel events of the current frame,
+/// is [`None`] if there were no events or they weren't passed to Egui (see
+/// [`EguiSettings::scroll_policy`]).
+///
+/// Is updated in [`EguiSet::ProcessInput`], systems that handle scrolling themselves (picking,
+/// camera zoom, etc) can run after it and skip the events consumed by Egui.
+///
+/// ```rust
+/// use bevy::{input::mouse::MouseWheel, prelude::*};
+/// use bevy_egui::EguiScrollCapture;
+///
+/// fn zoom_camera(mut mouse_wheel: EventReader<MouseWheel>, scroll_capture: Res<EguiScrollCapture>) {
+///     if scroll_capture.is_some() {
+///         mouse_wheel.clear();
+///         return;
+///     }
+///     for event in mouse_wheel.iter() {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, DerefMut, PartialEq, Eq)]
+pub struct EguiScrollCapture(pub Option<Entity>);
+
+/// A resource for dragging items between Egui contexts (for example, from an inventory window
+/// to a hotbar), as Egui's own drag-and-drop works only within a single context.
+///
+/// A source context starts a drag with [`EguiDragAndDrop::start_drag`] (usually when
+/// [`egui::Response::drag_started`] of an item is `true`), passing any payload and, optionally,
+/// the texture of the item. While dragging, contexts can draw the texture at the pointer, using
+/// [`EguiDragAndDrop::hovered`]. When the primary mouse button is released, the hovered context
+/// can accept the payload with [`EguiDragAndDrop::take_dropped`] during the same frame, after
+/// which the drag is cancelled. Is updated during [`EguiSet::ProcessInput`].
+#[derive(Resource, Default)]
+pub struct EguiDragAndDrop {
+    dragged: Option<EguiDraggedPayload>,
+    pub(crate) released: bool,
+    pub(crate) hovered: Option<(Entity, egui::Pos2)>,
+}
+
+/// An item dragged between Egui contexts, see [`EguiDragAndDrop`].
+pub struct EguiDraggedPayload {
+    /// Window entity of the context the item is dragged from.
+    pub source_window: Entity,
+    /// Texture that represents the item, if any.
+    pub texture_id: Option<egui::TextureId>,
+    payload: Box<dyn Any + Send + Sync>,
+}
+
+impl EguiDraggedPayload {
+    /// Returns the payload if it's of the type `T`.
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.downcast_ref()
+    }
+
+    /// Consumes the item and returns the payload if it's of the type `T`.
+    pub fn into_payload<T: Any>(self) -> Option<T> {
+        self.payload.downcast().ok().map(|payload| *payload)
+    }
+}
+
+impl EguiDragAndDrop {
+    /// Starts dragging an item from the context of a window, replacing the dragged item if there
+    /// is one.
+    pub fn start_drag(
+        &mut self,
+        source_window: Entity,
+        texture_id: Option<egui::TextureId>,
+        payload: impl Any + Send + Sync,
+    ) {
+        self.dragged = Some(EguiDraggedPayload {
+            source_window,
+            texture_id,
+            payload: Box::new(payload),
+        });
+        self.released = false;
+    }
+
+    /// Returns the dragged item, if any.
+    pub fn dragged(&self) -> Option<&EguiDraggedPayload> {
+        self.dragged.as_ref()
+    }
+
+    /// Returns `true` if an item is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.dragged.is_some()
+    }
+
+    /// Returns the window entity of the context under the pointer and the position of
+    /// the pointer in its points.
+    pub fn hovered(&self) -> Option<(Entity, egui::Pos2)> {
+        self.hovered
+    }
+
+    /// Returns the dragged item if it was dropped onto the context of the window this frame.
+    pub fn take_dropped(&mut self, window: Entity) -> Option<EguiDraggedPayload> {
+        if !self.released || self.hovered.map(|(hovered, _)| hovered) != Some(window) {
+            return None;
+        }
+        self.dragged.take()
+    }
+
+    /// Cancels the drag.
+    pub fn cancel(&mut self) {
+        self.dragged = None;
+        self.released = false;
+    }
+}
+
 /// A resource for storing `bevy_egui` user textures.
 #[derive(Clone, Resource, Default)]
 pub struct EguiUserTextures {
     textures: HashMap<Handle<Image>, u64>,
+    texture_options: HashMap<u64, EguiTextureOptions>,
     last_texture_id: u64,
 }
 
@@ -450,10 +1865,47 @@ impl EguiUserTextures {
         egui::TextureId::User(id)
     }
 
+    /// Same as [`EguiUserTextures::add_image`], but the texture is sampled with the given
+    /// filtering instead of the image's own sampler.
+    ///
+    /// Adding an image that is already added updates its options.
+    pub fn add_image_with_options(
+        &mut self,
+        image: Handle<Image>,
+        options: impl Into<EguiTextureOptions>,
+    ) -> egui::TextureId {
+        let texture_id = self.add_image(image);
+        if let egui::TextureId::User(id) = texture_id {
+            self.texture_options.insert(id, options.into());
+        }
+        texture_id
+    }
+
+    /// Adds an image (or reuses the texture id if it's already added) and returns its texture id
+    /// together with the UV rect of the region, which can be passed to [`egui::Image::uv`].
+    ///
+    /// The region and the image size (see [`Image::size`]) are in pixels. As all the regions of
+    /// an image share its texture id, removing the image removes all of them.
+    pub fn add_image_region(
+        &mut self,
+        image: Handle<Image>,
+        region: Rect,
+        image_size: Vec2,
+    ) -> (egui::TextureId, egui::Rect) {
+        let texture_id = self.add_image(image);
+        let min = region.min / image_size;
+        let max = region.max / image_size;
+        let uv = egui::Rect::from_min_max(egui::pos2(min.x, min.y), egui::pos2(max.x, max.y));
+        (texture_id, uv)
+    }
+
     /// Removes the image handle and an Egui texture id associated with it.
     pub fn remove_image(&mut self, image: &Handle<Image>) -> Option<egui::TextureId> {
         let id = self.textures.remove(image);
         log::debug!("Remove image (id: {:?}, handle: {:?})", id, image);
+        if let Some(id) = id {
+            self.texture_options.remove(&id);
+        }
         id.map(egui::TextureId::User)
     }
 
@@ -466,20 +1918,74 @@ impl EguiUserTextures {
     }
 }
 
+/// Sampler filtering of a user texture, see [`EguiUserTextures::add_image_with_options`].
+///
+/// Nearest filtering keeps pixel art sharp when it's scaled. Can be converted from
+/// [`egui::TextureOptions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EguiTextureOptions {
+    /// How to filter when magnifying (when texels are larger than pixels).
+    pub magnification: egui::TextureFilter,
+    /// How to filter when minifying (when texels are smaller than pixels).
+    pub minification: egui::TextureFilter,
+}
+
+impl EguiTextureOptions {
+    /// Linear magnification and minification.
+    pub const LINEAR: Self = Self {
+        magnification: egui::TextureFilter::Linear,
+        minification: egui::TextureFilter::Linear,
+    };
+
+    /// Nearest magnification and minification.
+    pub const NEAREST: Self = Self {
+        magnification: egui::TextureFilter::Nearest,
+        minification: egui::TextureFilter::Nearest,
+    };
+}
+
+impl Default for EguiTextureOptions {
+    fn default() -> Self {
+        Self::LINEAR
+    }
+}
+
+impl From<egui::TextureOptions> for EguiTextureOptions {
+    fn from(options: egui::TextureOptions) -> Self {
+        Self {
+            magnification: options.magnification,
+            minification: options.minification,
+        }
+    }
+}
+
 /// Stores physical size and scale factor, is used as a helper to calculate logical size.
-#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+///
+/// Is updated automatically for windows, contexts that aren't attached to windows have to be
+/// sized manually.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
 pub struct WindowSize {
     physical_width: f32,
     physical_height: f32,
     scale_factor: f32,
+    /// Is copied from [`EguiZoomFactor`], so that the render world scales the context as well.
+    zoom_factor: f32,
+}
+
+impl Default for WindowSize {
+    fn default() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
 }
 
 impl WindowSize {
-    fn new(physical_width: f32, physical_height: f32, scale_factor: f32) -> Self {
+    /// Creates the size from physical size and scale factor.
+    pub fn new(physical_width: f32, physical_height: f32, scale_factor: f32) -> Self {
         Self {
             physical_width,
             physical_height,
             scale_factor,
+            zoom_factor: 1.0,
         }
     }
 
@@ -494,9 +2000,150 @@ impl WindowSize {
     }
 }
 
+/// Zoom factor of an Egui context, which is applied on top of the other scale factors
+/// (`1.0` by default), see [`EguiSettings::effective_scale_factor`].
+///
+/// Is inserted when a context is initialized. Zoom requested by Egui (with
+/// [`egui::Context::set_pixels_per_point`], for example, by the keyboard shortcuts of
+/// [`egui::gui_zoom::zoom_with_keyboard_shortcuts`]) is stored in this component, so that it
+/// persists across frames.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Deref, DerefMut)]
+pub struct EguiZoomFactor(pub f32);
+
+impl Default for EguiZoomFactor {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Controls how the colors of an Egui context are blended into its render target.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EguiBlendMode {
+    /// Colors are premultiplied by alpha and blended with the `One, OneMinusSrcAlpha` factors,
+    /// which matches Egui's own renderers. The alpha of the target accumulates the coverage,
+    /// so that transparent targets (for example, windows captured as overlays) can be composited
+    /// with premultiplied alpha without dark fringes.
+    #[default]
+    Premultiplied,
+    /// Colors are unpremultiplied in the shader and blended with the `SrcAlpha, OneMinusSrcAlpha`
+    /// factors, for targets that are composited with straight alpha. Additive colors, which
+    /// Egui encodes as transparent colors with non-zero RGB, aren't drawn in this mode.
+    Straight,
+}
+
+/// Color that all the shapes of an Egui context are multiplied by when rendering (opaque white
+/// by default, which doesn't change the colors).
+///
+/// Changing the alpha fades the whole UI in or out without touching the widgets, for example,
+/// during screen transitions. The tint doesn't affect input.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Deref, DerefMut)]
+pub struct EguiGlobalTint(pub Color);
+
+impl Default for EguiGlobalTint {
+    fn default() -> Self {
+        Self(Color::WHITE)
+    }
+}
+
+/// Paint order of an Egui context relative to the contexts that are drawn into the same target.
+///
+/// Contexts with a greater order are drawn over the ones with a lesser order, contexts with equal
+/// orders are drawn in the order of their entities. Contexts without the component have
+/// the order of `0`.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EguiRenderOrder(pub i32);
+
+/// Keyboard focus priority of an Egui context's window.
+///
+/// Keyboard input is passed to the context of the focused window. When several windows claim
+/// focus during the same frame (for example, when they are created together), the window with
+/// the greatest priority receives it, windows with equal priorities fall back to the order of
+/// the focus events. Contexts without the component have the priority of `0`.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EguiFocusPriority(pub i32);
+
+/// Restricts an Egui context to a rectangle of its window.
+///
+/// The rectangle is specified in logical pixels, with the origin at the top left corner of the
+/// window. Egui's screen rect matches the size of the rectangle, pointer coordinates are offset
+/// by its position and rendering is clipped to it. Pointer input outside of the rectangle isn't
+/// passed to Egui, unless a button was pressed inside of it.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct EguiViewport(pub Rect);
+
+/// Scopes the Egui context of a window to a camera that renders into the window (for example,
+/// a minimap camera).
+///
+/// The [`EguiViewport`] of the context follows the viewport of the camera, so that Egui's screen
+/// rect, pointer input and rendering are restricted to it, as described for [`EguiViewport`].
+/// While the camera is inactive or doesn't exist, the context is disabled (see
+/// [`EguiDisabled`]), so that it's neither rendered nor receives input.
+///
+/// Cameras are referenced directly rather than matched by their `RenderLayers`, as a context
+/// can only follow a single viewport.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EguiTargetCamera(pub Entity);
+
+impl EguiViewport {
+    /// Returns the rectangle in physical pixels, clamped to the window bounds.
+    pub fn physical_rect(&self, window_size: WindowSize) -> Rect {
+        let window_rect = Rect::new(
+            0.0,
+            0.0,
+            window_size.physical_width,
+            window_size.physical_height,
+        );
+        Rect {
+            min: self.0.min * window_size.scale_factor,
+            max: self.0.max * window_size.scale_factor,
+        }
+        .intersect(window_rect)
+    }
+}
+
+/// Converts a logical window position (with the origin at the bottom left corner) to Egui points.
+fn window_to_egui_pos(
+    settings: &EguiSettings,
+    window_size: WindowSize,
+    viewport: Option<&EguiViewport>,
+    pos: Vec2,
+) -> egui::Pos2 {
+    let pos = Vec2::new(pos.x, window_size.height() - pos.y);
+    top_left_window_to_egui_pos(settings, window_size, viewport, pos)
+}
+
+/// Converts a logical window position with the origin at the top left corner (such as a touch
+/// position) to Egui points, is shared by the cursor and touch input.
+fn top_left_window_to_egui_pos(
+    settings: &EguiSettings,
+    window_size: WindowSize,
+    viewport: Option<&EguiViewport>,
+    mut pos: Vec2,
+) -> egui::Pos2 {
+    if let Some(viewport) = viewport {
+        pos -= viewport.physical_rect(window_size).min / window_size.scale_factor;
+    }
+    let pos = pos / settings.effective_scale_factor(window_size);
+    egui::pos2(pos.x, pos.y)
+}
+
+/// The inverse of [`window_to_egui_pos`].
+fn egui_to_window_pos(
+    settings: &EguiSettings,
+    window_size: WindowSize,
+    viewport: Option<&EguiViewport>,
+    pos: egui::Pos2,
+) -> Vec2 {
+    let mut pos = Vec2::new(pos.x, pos.y) * settings.effective_scale_factor(window_size);
+    if let Some(viewport) = viewport {
+        pos += viewport.physical_rect(window_size).min / window_size.scale_factor;
+    }
+    Vec2::new(pos.x, window_size.height() - pos.y)
+}
+
 /// The names of `bevy_egui` nodes.
 pub mod node {
-    /// The main egui pass.
+    /// The main egui pass, which renders the contexts of all the windows.
     pub const EGUI_PASS: &str = "egui_pass";
 }
 
@@ -527,18 +2174,46 @@ pub enum EguiSet {
 impl Plugin for EguiPlugin {
     fn build(&self, app: &mut App) {
         let world = &mut app.world;
-        world.init_resource::<EguiSettings>();
+        if let Some(settings) = &self.settings {
+            world.insert_resource(settings.clone());
+        } else {
+            world.init_resource::<EguiSettings>();
+        }
+        if !self.fallback_fonts.is_empty() {
+            world.insert_resource(EguiFallbackFonts(self.fallback_fonts.clone()));
+        }
         world.init_resource::<EguiManagedTextures>();
+        world.init_resource::<EguiManagedTexturesPartialUpdates>();
+        world.init_resource::<EguiTextureUploadCounters>();
         #[cfg(feature = "manage_clipboard")]
         world.init_resource::<EguiClipboard>();
         world.init_resource::<EguiUserTextures>();
         world.init_resource::<EguiMousePosition>();
+        world.init_resource::<EguiScrollCapture>();
+        world.init_resource::<EguiFocusedWindow>();
+        world.init_resource::<EguiDragAndDrop>();
+        world.init_resource::<EguiFrameStats>();
+        world.init_resource::<EguiRepaint>();
+        world.init_resource::<EguiDrawCalls>();
+        world.init_resource::<EguiCapturedImages>();
+        let egui_draw_calls = world.resource::<EguiDrawCalls>().clone();
+        let egui_captured_images = world.resource::<EguiCapturedImages>().clone();
+        app.add_event::<EguiCaptured>();
+        app.add_event::<EguiCaptureFailed>();
+        app.add_event::<EguiContextCreated>();
+        app.add_event::<EguiRepaintRequested>();
+        app.add_event::<EguiCopyEvent>();
+        app.add_event::<EguiImeRequest>();
+        app.add_event::<EguiOutputEvent>();
+        app.add_event::<EguiInputEvent>();
 
         app.add_startup_systems(
             (
                 setup_new_windows_system,
                 apply_system_buffers,
                 update_window_contexts_system,
+                apply_egui_theme_system,
+                apply_egui_fallback_fonts_system,
             )
                 .chain()
                 .in_set(EguiStartupSet::InitContexts)
@@ -547,8 +2222,11 @@ impl Plugin for EguiPlugin {
         app.add_systems(
             (
                 setup_new_windows_system,
+                update_target_camera_contexts_system,
                 apply_system_buffers,
                 update_window_contexts_system,
+                apply_egui_theme_system,
+                apply_egui_fallback_fonts_system,
             )
                 .chain()
                 .in_set(EguiSet::InitContexts)
@@ -556,37 +2234,115 @@ impl Plugin for EguiPlugin {
         );
         app.add_system(
             process_input_system
+                .run_if(
+                    resource_exists::<Input<KeyCode>>()
+                        .and_then(resource_exists::<Events<CursorMoved>>()),
+                )
+                .in_set(EguiSet::ProcessInput)
+                .after(InputSystem)
+                .after(EguiSet::InitContexts)
+                .in_base_set(CoreSet::PreUpdate),
+        );
+        app.add_system(
+            process_gamepad_input_system
+                .run_if(resource_exists::<EguiGamepadBindings>())
                 .in_set(EguiSet::ProcessInput)
+                .after(process_input_system)
                 .after(InputSystem)
                 .after(EguiSet::InitContexts)
                 .in_base_set(CoreSet::PreUpdate),
         );
         app.add_system(
-            begin_frame_system
-                .in_set(EguiSet::BeginFrame)
-                .after(EguiSet::ProcessInput)
+            process_input_events_system
+                .in_set(EguiSet::ProcessInput)
+                .after(process_input_system)
+                .after(EguiSet::InitContexts)
                 .in_base_set(CoreSet::PreUpdate),
         );
+        app.add_system(
+            update_drag_and_drop_system
+                .in_set(EguiSet::ProcessInput)
+                .after(process_input_system)
+                .after(InputSystem)
+                .in_base_set(CoreSet::PreUpdate),
+        );
+        if !self.run_manually && self.run_in_fixed_update {
+            app.add_systems(
+                (apply_fixed_time_system, begin_frame_system)
+                    .chain()
+                    .in_set(EguiSet::BeginFrame)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+            app.add_system(
+                end_frame_system
+                    .in_set(EguiSet::ProcessOutput)
+                    .after(EguiSet::BeginFrame)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+            app.add_system(
+                draw_input_debug_overlay_system
+                    .run_if(resource_exists::<EguiInputDebugOverlay>())
+                    .after(EguiSet::BeginFrame)
+                    .before(EguiSet::ProcessOutput)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
+        } else if !self.run_manually {
+            app.add_system(
+                begin_frame_system
+                    .in_set(EguiSet::BeginFrame)
+                    .after(EguiSet::ProcessInput)
+                    .in_base_set(CoreSet::PreUpdate),
+            );
+            app.add_system(
+                end_frame_system
+                    .in_set(EguiSet::ProcessOutput)
+                    .before(process_output_system)
+                    .in_base_set(CoreSet::PostUpdate),
+            );
+            app.add_system(
+                draw_input_debug_overlay_system
+                    .run_if(resource_exists::<EguiInputDebugOverlay>())
+                    .after(EguiSet::BeginFrame)
+                    .in_base_set(CoreSet::PreUpdate),
+            );
+        }
         app.add_system(
             process_output_system
                 .in_set(EguiSet::ProcessOutput)
                 .in_base_set(CoreSet::PostUpdate),
         );
+        #[cfg(feature = "reactive_repaint")]
+        app.add_system(
+            wake_up_for_repaint_system
+                .after(EguiSet::ProcessOutput)
+                .in_base_set(CoreSet::PostUpdate),
+        );
         app.add_system(
             update_egui_textures_system
+                .run_if(resource_exists::<Assets<Image>>())
                 .after(EguiSet::ProcessOutput)
                 .in_base_set(CoreSet::PostUpdate),
         );
-        app.add_system(free_egui_textures_system.in_base_set(CoreSet::Last));
-
-        let mut shaders = app.world.resource_mut::<Assets<Shader>>();
-        shaders.set_untracked(
-            EGUI_SHADER_HANDLE,
-            Shader::from_wgsl(include_str!("egui.wgsl")),
+        app.add_system(receive_egui_captures_system.in_base_set(CoreSet::PreUpdate));
+        app.add_system(
+            free_egui_textures_system
+                .run_if(resource_exists::<Assets<Image>>())
+                .in_base_set(CoreSet::Last),
         );
+        app.add_system(free_removed_contexts_system.in_base_set(CoreSet::Last));
+
+        if let Some(mut shaders) = app.world.get_resource_mut::<Assets<Shader>>() {
+            shaders.set_untracked(
+                EGUI_SHADER_HANDLE,
+                Shader::from_wgsl(include_str!("egui.wgsl")),
+            );
+        }
 
         if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
+                .insert_resource(egui_draw_calls)
+                .insert_resource(egui_captured_images)
+                .init_resource::<EguiPendingCaptures>()
                 .init_resource::<egui_node::EguiPipeline>()
                 .init_resource::<SpecializedRenderPipelines<EguiPipeline>>()
                 .init_resource::<EguiTransforms>()
@@ -594,7 +2350,7 @@ impl Plugin for EguiPlugin {
                     (
                         render_systems::extract_egui_render_data_system,
                         render_systems::extract_egui_textures_system,
-                        render_systems::setup_new_windows_render_system,
+                        render_systems::extract_egui_texture_partial_updates_system,
                     )
                         .into_configs()
                         .in_schedule(ExtractSchedule),
@@ -602,8 +2358,47 @@ impl Plugin for EguiPlugin {
                 .add_system(
                     render_systems::prepare_egui_transforms_system.in_set(RenderSet::Prepare),
                 )
+                .add_system(
+                    render_systems::write_egui_texture_partial_updates_system
+                        .in_set(PrepareAssetSet::PostAssetPrepare),
+                )
                 .add_system(render_systems::queue_bind_groups_system.in_set(RenderSet::Queue))
-                .add_system(render_systems::queue_pipelines_system.in_set(RenderSet::Queue));
+                .add_system(render_systems::queue_pipelines_system.in_set(RenderSet::Queue))
+                .add_system(render_systems::map_egui_captures_system.in_set(RenderSet::Cleanup));
+
+            let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
+            if self.render_node_placement != EguiRenderNodePlacement::Disabled {
+                render_graph.add_node(node::EGUI_PASS, EguiNode::new());
+            }
+            match &self.render_node_placement {
+                EguiRenderNodePlacement::AfterCameraDriver => {
+                    render_graph.add_node_edge(CAMERA_DRIVER, node::EGUI_PASS);
+                }
+                EguiRenderNodePlacement::After(nodes) => {
+                    for label in nodes {
+                        if let Err(err) =
+                            render_graph.try_add_node_edge(label.to_string(), node::EGUI_PASS)
+                        {
+                            log::error!(
+                                "Failed to run the Egui render node after '{label}': {err:?}"
+                            );
+                        }
+                    }
+                }
+                EguiRenderNodePlacement::Before(nodes) => {
+                    render_graph.add_node_edge(CAMERA_DRIVER, node::EGUI_PASS);
+                    for label in nodes {
+                        if let Err(err) =
+                            render_graph.try_add_node_edge(node::EGUI_PASS, label.to_string())
+                        {
+                            log::error!(
+                                "Failed to run the Egui render node before '{label}': {err:?}"
+                            );
+                        }
+                    }
+                }
+                EguiRenderNodePlacement::Manual | EguiRenderNodePlacement::Disabled => {}
+            }
         }
     }
 }
@@ -624,45 +2419,421 @@ pub struct EguiContextQuery {
     pub egui_output: &'static mut EguiOutput,
     /// Stores physical size of the window and its scale factor.
     pub window_size: &'static mut WindowSize,
-    /// [`Window`] component.
-    pub window: &'static mut Window,
+    /// [`Window`] component, is [`None`] for contexts that aren't attached to windows.
+    pub window: Option<&'static mut Window>,
+    /// [`Camera`] component, is [`Some`] for contexts that are drawn into camera targets.
+    pub camera: Option<&'static Camera>,
+    /// Restricts the context to a rectangle of the window, see [`EguiViewport`].
+    pub viewport: Option<&'static EguiViewport>,
+    /// Paint order of the context, see [`EguiRenderOrder`].
+    pub render_order: Option<&'static EguiRenderOrder>,
+    /// Blending of the context, see [`EguiBlendMode`].
+    pub blend_mode: Option<&'static EguiBlendMode>,
+    /// Color multiplier of the context, see [`EguiGlobalTint`].
+    pub global_tint: Option<&'static EguiGlobalTint>,
+    /// Keyboard focus priority of the context, see [`EguiFocusPriority`].
+    pub focus_priority: Option<&'static EguiFocusPriority>,
+    /// Controls which input is passed to the context.
+    pub input_enabled: Option<&'static EguiInputEnabled>,
+    /// Zoom factor of the context, see [`EguiZoomFactor`].
+    pub zoom_factor: Option<&'static EguiZoomFactor>,
+    /// Is [`Some`] if the context is disabled, see [`EguiDisabled`].
+    pub disabled: Option<&'static EguiDisabled>,
+    /// Is [`Some`] if rendering of the context is disabled, see [`EguiRenderDisabled`].
+    pub render_disabled: Option<&'static EguiRenderDisabled>,
+    /// Output of the ended frames that hasn't been processed yet.
+    pub full_output: &'static mut EguiFullOutput,
+    /// Selects the clock that drives the context's time.
+    pub time_source: Option<&'static EguiTimeSource>,
+}
+
+/// Egui frame statistics, which can be used for profiling.
+///
+/// Is updated during the [`EguiSet::ProcessOutput`] system. As rendering happens after the main
+/// app update, draw calls reflect the last rendered frame.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct EguiFrameStats {
+    /// Number of Egui contexts that ran a frame.
+    pub context_count: usize,
+    /// Number of tessellated vertices.
+    pub vertices: usize,
+    /// Number of tessellated indices.
+    pub indices: usize,
+    /// Number of draw calls issued by the render node.
+    pub draw_calls: usize,
+    /// Time passed between the [`EguiSet::BeginFrame`] system (or the first
+    /// [`EguiManualFrames::begin_frame`] call if frames are run manually) and the end of
+    /// tessellation during [`EguiSet::ProcessOutput`], which includes running the UI systems.
+    pub cpu_run_time: Duration,
+    /// Statistics of each context, keyed by their window entities.
+    pub contexts: HashMap<Entity, EguiContextFrameStats>,
+    frame_start: Option<Instant>,
+}
+
+/// Tracks when Egui contexts want to be repainted, see [`egui::FullOutput::repaint_after`].
+///
+/// Is updated during the [`EguiSet::ProcessOutput`] system, which also sends
+/// [`EguiRepaintRequested`] for each window whose context requested a repaint immediately, or
+/// whose repaint scheduled by a previous frame is due (together with a single
+/// [`bevy::window::RequestRedraw`]).
+///
+/// A redraw request can't wake up an idle app, so with the `reactive_repaint` feature (which
+/// requires a winit backend, such as `bevy/x11`, to be enabled) the `max_wait` of reactive
+/// `WinitSettings` update modes is shortened until the [`EguiRepaint::deadline`] instead, see
+/// `wake_up_for_repaint_system`. Apps that use other runners can read
+/// [`EguiRepaint::repaint_after`] to limit how long they wait for the next update, so that Egui
+/// animations don't freeze.
+///
+/// Animations and widgets such as [`egui::Spinner`] request a repaint every frame while they are
+/// shown, so redraws are requested continuously while anything animates and stop once the UI is
+/// idle, no per-context flag is needed.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct EguiRepaint {
+    /// The minimum delay requested by the contexts during the last frame. Zero means that
+    /// Egui wants to repaint continuously, [`Duration::MAX`] means that it's idle.
+    pub repaint_after: Duration,
+    /// When the next repaint is due, is [`None`] if Egui is idle.
+    pub deadline: Option<Instant>,
+    /// When the next repaint of each context is due, keyed by their window entities.
+    /// Idle contexts aren't included.
+    pub deadlines: HashMap<Entity, Instant>,
+}
+
+/// Is sent when the Egui context of a window requests a repaint (via
+/// [`egui::Context::request_repaint`] or [`egui::Context::request_repaint_after`]).
+///
+/// Bevy can't redraw a single window, so [`bevy::window::RequestRedraw`] is sent as well, but
+/// apps that render windows on demand can use this event to tell which ones need an update.
+#[derive(Clone, Copy, Debug)]
+pub struct EguiRepaintRequested {
+    /// Window entity of the context that requested a repaint.
+    pub window: Entity,
+}
+
+/// Frame statistics of a single Egui context, see [`EguiFrameStats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EguiContextFrameStats {
+    /// Number of tessellated vertices.
+    pub vertices: usize,
+    /// Number of tessellated indices.
+    pub indices: usize,
+    /// Number of draw calls issued by the render node.
+    pub draw_calls: usize,
+}
+
+impl EguiFrameStats {
+    pub(crate) fn begin_frame(&mut self) {
+        self.frame_start = Some(Instant::now());
+    }
+
+    /// Several frames can be run manually during an update, the time is measured from the first.
+    fn begin_manual_frame(&mut self) {
+        self.frame_start.get_or_insert_with(Instant::now);
+    }
 }
 
 /// Contains textures allocated and painted by Egui.
 #[derive(Resource, Deref, DerefMut, Default)]
 pub struct EguiManagedTextures(pub HashMap<(Entity, u64), EguiManagedTexture>);
 
+impl EguiManagedTextures {
+    /// Egui texture id and the image of the font atlas of a window's context.
+    ///
+    /// Only the GPU texture is kept up to date: partial updates are written directly to it and
+    /// aren't applied to the CPU-side [`Image`] asset, which thus contains the atlas as it was
+    /// when it was last allocated (use [`EguiManagedTexture::color_image`] for the current
+    /// contents). The atlas is also reallocated when fonts change or it needs to grow, so the
+    /// returned handle is weak and shouldn't be stored across frames. In the render world, the
+    /// texture view can be retrieved from [`bevy::render::render_asset::RenderAssets<Image>`].
+    ///
+    /// Returns [`None`] if the context doesn't exist or hasn't painted its fonts yet.
+    #[must_use]
+    pub fn font_texture(&self, window: Entity) -> Option<(egui::TextureId, Handle<Image>)> {
+        // Egui allocates the font atlas first, so it always has the default id (`Managed(0)`).
+        self.get(&(window, 0))
+            .map(|texture| (egui::TextureId::default(), texture.handle.clone_weak()))
+    }
+}
+
 /// Represents a texture allocated and painted by Egui.
 pub struct EguiManagedTexture {
     /// Assets store handle.
     pub handle: Handle<Image>,
     /// Stored in full so we can do partial updates (which bevy doesn't support).
+    ///
+    /// Partial updates aren't applied to the image asset (which would cause re-uploading the whole
+    /// texture), so this is the only up-to-date CPU copy of the texture.
+    pub color_image: egui::ColorImage,
+}
+
+/// Changed sub-rectangles of Egui managed textures, which are written directly to the GPU textures
+/// instead of re-uploading whole images.
+///
+/// The list gets populated by [`update_egui_textures_system`] and is reset on the next frame.
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct EguiManagedTexturesPartialUpdates(pub Vec<EguiManagedTexturePartialUpdate>);
+
+/// Represents a changed sub-rectangle of an Egui managed texture.
+pub struct EguiManagedTexturePartialUpdate {
+    /// Assets store handle of the updated texture.
+    pub handle: Handle<Image>,
+    /// Position of the top left corner of the updated region.
+    pub pos: [usize; 2],
+    /// New contents of the region.
     pub color_image: egui::ColorImage,
 }
 
+/// Counts uploads of Egui managed textures (such as the font atlas).
+///
+/// Full uploads happen when Egui allocates a texture or reallocates it (for example, when fonts
+/// or DPI change), partial uploads are the ones that update only a changed region.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EguiTextureUploadCounters {
+    /// Number of full texture uploads since the app start.
+    pub full_uploads: u64,
+    /// Number of partial texture uploads since the app start.
+    pub partial_uploads: u64,
+}
+
+/// Is sent when an Egui context is initialized for a window (or a windowless context entity).
+///
+/// The event is sent during [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`]),
+/// before the first frame of the context begins, so systems that run between these sets can
+/// configure the context (set its style, install fonts, etc) before it's used.
+#[derive(Clone, Copy, Debug)]
+pub struct EguiContextCreated {
+    /// Window entity of the new context.
+    pub window: Entity,
+}
+
+/// Is sent when Egui copies (or cuts) text, see [`egui::PlatformOutput::copied_text`].
+///
+/// With the `manage_clipboard` feature, the text is also put into the system clipboard
+/// (`EguiClipboard`). Apps can subscribe to this event to integrate with their own clipboard,
+/// for example on web targets without `web_sys_unstable_apis`.
+#[derive(Clone, Debug)]
+pub struct EguiCopyEvent {
+    /// Window entity of the context that copied the text.
+    pub window: Entity,
+    /// The copied text.
+    pub text: String,
+}
+
+/// Is sent for every widget interaction reported by Egui (see [`egui::PlatformOutput::events`]),
+/// which can be used for logging UI interactions without instrumenting every widget.
+///
+/// Egui reports clicks, focus and value changes of the widgets that describe themselves
+/// (with [`egui::Response::widget_info`]), which the built-in widgets do. The widget type
+/// (for example, [`egui::WidgetType::Link`]) and label are available from
+/// [`egui::output::OutputEvent::widget_info`].
+#[derive(Clone, Debug)]
+pub struct EguiOutputEvent {
+    /// Window entity of the context.
+    pub window: Entity,
+    /// The interaction.
+    pub event: egui::output::OutputEvent,
+}
+
+/// Passes an event to the Egui context of a window, which is the supported way of feeding input
+/// from custom devices (for example, a MIDI controller mapped to UI actions).
+///
+/// The events are added to the [`EguiInput`] of the context during the [`EguiSet::ProcessInput`]
+/// system, after the events gathered from Bevy input, and are subject to [`EguiEventFilter`] and
+/// [`EguiSettings::raw_input_hook`] like them. Events sent to missing or disabled contexts are
+/// dropped. To pass the events of the current update, send them before [`EguiSet::ProcessInput`].
+#[derive(Clone, Debug)]
+pub struct EguiInputEvent {
+    /// Window entity of the context.
+    pub window: Entity,
+    /// The event.
+    pub event: egui::Event,
+}
+
+/// Is sent when Egui starts or stops expecting text input, or moves the text cursor.
+///
+/// Unless [`EguiSettings::manage_ime`] is disabled, the IME of the window is updated accordingly,
+/// apps can use this event to drive the IME themselves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EguiImeRequest {
+    /// Window entity of the context.
+    pub window: Entity,
+    /// Whether a text field has focus and the IME should be enabled.
+    pub enabled: bool,
+    /// Position of the text cursor in logical pixels of the window, with the origin at the top
+    /// left corner (which matches [`Window::ime_position`]).
+    pub cursor_position: Option<Vec2>,
+}
+
 /// Adds bevy_egui components to newly created windows.
+///
+/// Also initializes contexts that were created manually by inserting [`EguiContext`] to entities
+/// without windows (see [`EguiContext`]).
+#[allow(clippy::type_complexity)]
 pub fn setup_new_windows_system(
     mut commands: Commands,
     new_windows: Query<Entity, (Added<Window>, Without<EguiContext>)>,
+    new_windowless_contexts: Query<
+        (
+            Entity,
+            &EguiContext,
+            Option<&WindowSize>,
+            Option<&EguiInputEnabled>,
+            Option<&EguiZoomFactor>,
+        ),
+        (Without<Window>, Without<EguiInput>),
+    >,
+    egui_settings: Res<EguiSettings>,
+    mut egui_context_created: EventWriter<EguiContextCreated>,
 ) {
     for window in new_windows.iter() {
+        let context = EguiContext::default();
+        if let Some(egui_options) = &egui_settings.egui_options {
+            egui_options.apply(&context.0);
+        }
         commands.entity(window).insert((
-            EguiContext::default(),
+            context,
             EguiMousePosition::default(),
             EguiRenderOutput::default(),
             EguiInput::default(),
             EguiOutput::default(),
+            EguiFullOutput::default(),
             WindowSize::default(),
+            EguiInputEnabled::default(),
+            EguiZoomFactor::default(),
+        ));
+        egui_context_created.send(EguiContextCreated { window });
+    }
+
+    for (entity, context, window_size, input_enabled, zoom_factor) in new_windowless_contexts.iter()
+    {
+        if let Some(egui_options) = &egui_settings.egui_options {
+            egui_options.apply(&context.0);
+        }
+        commands.entity(entity).insert((
+            EguiMousePosition::default(),
+            EguiRenderOutput::default(),
+            EguiInput::default(),
+            EguiOutput::default(),
+            EguiFullOutput::default(),
+            window_size.copied().unwrap_or_default(),
+            input_enabled.copied().unwrap_or_default(),
+            zoom_factor.copied().unwrap_or_default(),
         ));
+        egui_context_created.send(EguiContextCreated { window: entity });
+    }
+}
+
+/// Requests capturing what an Egui context renders as an image.
+///
+/// Insert this component into a window entity to capture the next frame of its Egui context.
+/// The context is drawn into a separate texture on a transparent background, which is then read
+/// back asynchronously and delivered via the [`EguiCaptured`] event a few frames later. The
+/// component is removed once the image is delivered (or [`EguiCaptureFailed`] is sent), insert
+/// it again to capture another frame.
+///
+/// The request is retried every frame until the context can be drawn into the capture texture
+/// (for example, while the capture pipeline is being compiled). It fails if the context is
+/// [disabled](EguiDisabled), isn't [rendered](EguiRenderDisabled) or its window is zero-sized.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct EguiCaptureRequest;
+
+/// Is sent when an image requested with [`EguiCaptureRequest`] is ready.
+#[derive(Clone, Debug)]
+pub struct EguiCaptured {
+    /// Window entity of the captured context.
+    pub window: Entity,
+    /// Rendered output of the context in the `Rgba8UnormSrgb` format with premultiplied alpha.
+    pub image: Image,
+}
+
+/// Is sent when an image requested with [`EguiCaptureRequest`] couldn't be captured or read
+/// back from the GPU, read back errors are logged.
+#[derive(Clone, Copy, Debug)]
+pub struct EguiCaptureFailed {
+    /// Window entity of the captured context.
+    pub window: Entity,
+}
+
+/// Sends [`EguiCaptured`] (or [`EguiCaptureFailed`]) events for images read back by the render
+/// world.
+#[allow(clippy::type_complexity)]
+pub fn receive_egui_captures_system(
+    mut commands: Commands,
+    egui_captured_images: Res<EguiCapturedImages>,
+    mut egui_captured: EventWriter<EguiCaptured>,
+    mut egui_capture_failed: EventWriter<EguiCaptureFailed>,
+    unrendered_capture_requests: Query<
+        Entity,
+        (
+            With<EguiCaptureRequest>,
+            Or<(With<EguiDisabled>, With<EguiRenderDisabled>)>,
+        ),
+    >,
+) {
+    // Contexts that aren't extracted for rendering can't be captured.
+    for window in unrendered_capture_requests.iter() {
+        commands.entity(window).remove::<EguiCaptureRequest>();
+        egui_capture_failed.send(EguiCaptureFailed { window });
+    }
+    for (window, image) in egui_captured_images.0.lock().unwrap().drain(..) {
+        if let Some(mut entity_commands) = commands.get_entity(window) {
+            entity_commands.remove::<EguiCaptureRequest>();
+        }
+        match image {
+            Some(image) => egui_captured.send(EguiCaptured { window, image }),
+            None => egui_capture_failed.send(EguiCaptureFailed { window }),
+        }
+    }
+}
+
+/// Applies [`EguiTheme`] to the contexts.
+pub fn apply_egui_theme_system(
+    egui_theme: Option<Res<EguiTheme>>,
+    mut contexts: Query<(&mut EguiContext, Ref<EguiInput>)>,
+) {
+    let Some(egui_theme) = egui_theme else {
+        return;
+    };
+    for (mut context, egui_input) in contexts.iter_mut() {
+        // Contexts get their input component once they are initialized.
+        if egui_theme.is_changed() || egui_input.is_added() {
+            context.get_mut().set_visuals(egui_theme.0.clone());
+        }
+    }
+}
+
+/// Installs [`EguiFallbackFonts`] into the contexts.
+pub fn apply_egui_fallback_fonts_system(
+    fallback_fonts: Option<Res<EguiFallbackFonts>>,
+    mut contexts: Query<(&mut EguiContext, Ref<EguiInput>)>,
+) {
+    let Some(fallback_fonts) = fallback_fonts else {
+        return;
+    };
+    let mut font_definitions = None;
+    for (mut context, egui_input) in contexts.iter_mut() {
+        // Contexts get their input component once they are initialized.
+        if fallback_fonts.is_changed() || egui_input.is_added() {
+            let font_definitions =
+                font_definitions.get_or_insert_with(|| fallback_fonts.font_definitions());
+            context.get_mut().set_fonts(font_definitions.clone());
+        }
     }
 }
 
 /// Updates textures painted by Egui.
 pub fn update_egui_textures_system(
-    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput), With<Window>>,
+    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput)>,
     mut egui_managed_textures: ResMut<EguiManagedTextures>,
+    mut egui_partial_updates: ResMut<EguiManagedTexturesPartialUpdates>,
+    mut egui_upload_counters: ResMut<EguiTextureUploadCounters>,
     mut image_assets: ResMut<Assets<Image>>,
+    egui_settings: Res<EguiSettings>,
+    render_device: Option<Res<RenderDevice>>,
 ) {
+    egui_partial_updates.clear();
+    let max_texture_side = egui_settings.effective_max_texture_side(render_device.as_deref());
+
     for (window_id, mut egui_render_output) in egui_render_output.iter_mut() {
         let set_textures = std::mem::take(&mut egui_render_output.textures_delta.set);
 
@@ -679,17 +2850,39 @@ pub fn update_egui_textures_system(
                 if let Some(managed_texture) =
                     egui_managed_textures.get_mut(&(window_id, texture_id))
                 {
-                    // TODO: when bevy supports it, only update the part of the texture that changes.
                     update_image_rect(&mut managed_texture.color_image, pos, &color_image);
-                    let image = egui_node::color_image_as_bevy_image(&managed_texture.color_image);
-                    managed_texture.handle = image_assets.add(image);
+                    egui_partial_updates.push(EguiManagedTexturePartialUpdate {
+                        handle: managed_texture.handle.clone(),
+                        pos,
+                        color_image,
+                    });
+                    egui_upload_counters.partial_uploads += 1;
                 } else {
                     log::warn!("Partial update of a missing texture (id: {:?})", texture_id);
                 }
             } else {
                 // Full update.
+                if let Some(max_texture_side) = max_texture_side {
+                    if color_image.width() > max_texture_side
+                        || color_image.height() > max_texture_side
+                    {
+                        log::error!(
+                            "Egui texture (id: {:?}) is too large: {:?}, the maximum size is {}",
+                            texture_id,
+                            color_image.size,
+                            max_texture_side
+                        );
+                        continue;
+                    }
+                }
                 let image = egui_node::color_image_as_bevy_image(&color_image);
                 let handle = image_assets.add(image);
+                egui_upload_counters.full_uploads += 1;
+                log::debug!(
+                    "Full upload of an Egui texture (id: {:?}, size: {:?})",
+                    texture_id,
+                    color_image.size
+                );
                 egui_managed_textures.insert(
                     (window_id, texture_id),
                     EguiManagedTexture {
@@ -712,7 +2905,7 @@ pub fn update_egui_textures_system(
 
 fn free_egui_textures_system(
     mut egui_user_textures: ResMut<EguiUserTextures>,
-    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput), With<Window>>,
+    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput)>,
     mut egui_managed_textures: ResMut<EguiManagedTextures>,
     mut image_assets: ResMut<Assets<Image>>,
     mut image_events: EventReader<AssetEvent<Image>>,
@@ -736,6 +2929,36 @@ fn free_egui_textures_system(
     }
 }
 
+/// Frees the managed textures and other state of removed contexts (including the contexts of
+/// closed windows).
+fn free_removed_contexts_system(
+    mut removed_contexts: RemovedComponents<EguiContext>,
+    mut egui_managed_textures: ResMut<EguiManagedTextures>,
+    mut egui_repaint: ResMut<EguiRepaint>,
+    mut egui_mouse_position: ResMut<EguiMousePosition>,
+    mut egui_focused_window: ResMut<EguiFocusedWindow>,
+    mut image_assets: Option<ResMut<Assets<Image>>>,
+) {
+    for window in removed_contexts.iter() {
+        egui_managed_textures.retain(|&(window_id, _), managed_texture| {
+            if window_id != window {
+                return true;
+            }
+            if let Some(image_assets) = image_assets.as_mut() {
+                image_assets.remove(&managed_texture.handle);
+            }
+            false
+        });
+        egui_repaint.deadlines.remove(&window);
+        if egui_mouse_position.is_some_and(|(window_id, _)| window_id == window) {
+            egui_mouse_position.take();
+        }
+        if **egui_focused_window == Some(window) {
+            egui_focused_window.take();
+        }
+    }
+}
+
 /// Egui's render graph config.
 pub struct RenderGraphConfig {
     /// Target window.
@@ -749,9 +2972,14 @@ mod tests {
     use super::*;
     use bevy::{
         app::PluginGroup,
+        asset::{AddAsset, AssetPlugin, HandleId},
+        ecs::system::SystemState,
+        input::InputPlugin,
         render::{settings::WgpuSettings, RenderPlugin},
+        utils::Duration,
+        window::{ExitCondition, WindowPlugin},
         winit::WinitPlugin,
-        DefaultPlugins,
+        DefaultPlugins, MinimalPlugins,
     };
 
     #[test]
@@ -759,6 +2987,407 @@ mod tests {
         version_sync::assert_markdown_deps_updated!("README.md");
     }
 
+    /// Returns an app with a single Egui context that isn't attached to a window, the first
+    /// update has already run.
+    pub(crate) fn test_app() -> (App, Entity) {
+        test_app_with(EguiPlugin::default())
+    }
+
+    /// Same as [`test_app`], but with a configured [`EguiPlugin`].
+    pub(crate) fn test_app_with(egui_plugin: EguiPlugin) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(InputPlugin)
+            .add_plugin(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..Default::default()
+            })
+            .add_plugin(egui_plugin);
+        let entity = app
+            .world
+            .spawn((EguiContext::default(), WindowSize::new(640.0, 480.0, 1.0)))
+            .id();
+        app.update();
+        (app, entity)
+    }
+
+    #[test]
+    fn test_contexts_access() {
+        let (mut app, entity) = test_app();
+        // `EguiContexts` must not conflict with the other bevy_egui resources and components.
+        app.add_system(
+            move |mut contexts: EguiContexts,
+                  mut egui_settings: ResMut<EguiSettings>,
+                  _managed_textures: ResMut<EguiManagedTextures>,
+                  _components: Query<(
+                &mut EguiInput,
+                &mut EguiOutput,
+                &mut WindowSize,
+                &mut EguiInputEnabled,
+            )>| {
+                egui_settings.scale_factor = 2.0;
+                let _ = contexts.ctx_for_window_mut(entity);
+            },
+        );
+        app.update();
+        assert_eq!(app.world.resource::<EguiSettings>().scale_factor, 2.0);
+    }
+
+    #[test]
+    fn test_failed_capture() {
+        let (mut app, entity) = test_app();
+        app.world.entity_mut(entity).insert(EguiCaptureRequest);
+        // Is what the render world reports when reading back the texture fails.
+        app.world
+            .resource::<EguiCapturedImages>()
+            .0
+            .lock()
+            .unwrap()
+            .push((entity, None));
+        app.update();
+
+        assert!(!app.world.entity(entity).contains::<EguiCaptureRequest>());
+        let failed = app.world.resource::<Events<EguiCaptureFailed>>();
+        assert_eq!(
+            failed
+                .iter_current_update_events()
+                .map(|event| event.window)
+                .collect::<Vec<_>>(),
+            [entity]
+        );
+        assert!(app.world.resource::<Events<EguiCaptured>>().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_context_capture_fails() {
+        let (mut app, entity) = test_app();
+        app.world
+            .entity_mut(entity)
+            .insert((EguiDisabled, EguiCaptureRequest));
+        app.update();
+
+        assert!(!app.world.entity(entity).contains::<EguiCaptureRequest>());
+        let failed = app.world.resource::<Events<EguiCaptureFailed>>();
+        assert_eq!(
+            failed
+                .iter_current_update_events()
+                .map(|event| event.window)
+                .collect::<Vec<_>>(),
+            [entity]
+        );
+    }
+
+    #[test]
+    fn test_windowless_context() {
+        let (mut app, entity) = test_app();
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        assert_eq!(
+            ctx.get_mut().screen_rect(),
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(640.0, 480.0))
+        );
+    }
+
+    #[test]
+    fn test_removed_contexts_freed() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .add_plugin(EguiPlugin::default());
+        let mut remove_context = SystemState::<EguiContexts>::new(&mut app.world);
+
+        for _ in 0..3 {
+            let contexts = (0..4)
+                .map(|_| {
+                    app.world
+                        .spawn((EguiContext::default(), WindowSize::new(640.0, 480.0, 1.0)))
+                        .id()
+                })
+                .collect::<Vec<_>>();
+            app.update();
+            // Every context has its own font texture.
+            assert_eq!(app.world.resource::<EguiManagedTextures>().len(), 4);
+
+            // Half of the contexts are removed, and the rest are despawned as if their windows
+            // were closed.
+            let mut egui_contexts = remove_context.get_mut(&mut app.world);
+            for context in &contexts[..2] {
+                assert!(egui_contexts.remove_context(*context));
+            }
+            remove_context.apply(&mut app.world);
+            for context in &contexts[2..] {
+                app.world.despawn(*context);
+            }
+            app.update();
+            assert!(app.world.resource::<EguiManagedTextures>().is_empty());
+            assert_eq!(app.world.resource::<Assets<Image>>().len(), 0);
+        }
+    }
+
+    #[cfg(feature = "default_fonts")]
+    #[test]
+    fn test_fallback_fonts() {
+        let fallback_font = egui::FontDefinitions::default().font_data["Hack"].clone();
+        let (mut app, entity) =
+            test_app_with(EguiPlugin::default().with_fallback_font("Fallback", fallback_font));
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        let families = ctx
+            .get_mut()
+            .fonts(|fonts| fonts.lock().fonts.definitions().families.clone());
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            assert_eq!(families[&family].last().unwrap(), "Fallback");
+        }
+    }
+
+    #[cfg(feature = "immutable_ctx")]
+    #[test]
+    fn test_contexts_read() {
+        let (mut app, entity) = test_app();
+        app.add_system(move |contexts: EguiContextsRead| {
+            egui::Window::new("Window").show(contexts.ctx_for_window(entity), |ui| {
+                ui.label("Label");
+            });
+            assert!(contexts.try_ctx().is_none());
+            assert_eq!(contexts.iter_contexts().count(), 1);
+        });
+        app.update();
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        assert!(ctx.get_mut().used_rect().is_positive());
+    }
+
+    #[test]
+    fn test_manual_frame_stats() {
+        let (mut app, entity) = test_app_with(EguiPlugin::default().run_manually(true));
+        app.add_system(move |mut manual_frames: EguiManualFrames| {
+            egui::Window::new("Stats").show(manual_frames.begin_frame(entity), |ui| {
+                ui.label("Stats");
+            });
+            std::thread::sleep(Duration::from_millis(2));
+            manual_frames.end_frame(entity);
+        });
+        app.update();
+
+        let frame_stats = app.world.resource::<EguiFrameStats>();
+        assert_eq!(frame_stats.context_count, 1);
+        assert!(frame_stats.cpu_run_time >= Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_contexts_have_separate_memory() {
+        let (mut app, first) = test_app();
+        let field_id = egui::Id::new("field");
+        let second = app
+            .world
+            .spawn((EguiContext::default(), WindowSize::new(640.0, 480.0, 1.0)))
+            .id();
+        app.add_system(move |mut contexts: EguiContexts| {
+            for window in [first, second] {
+                egui::Window::new("Same").default_pos((100.0, 100.0)).show(
+                    contexts.ctx_for_window_mut(window),
+                    |ui| {
+                        ui.add(egui::TextEdit::singleline(&mut String::new()).id(field_id));
+                    },
+                );
+            }
+        });
+        app.update();
+
+        let mut ctx = app.world.get_mut::<EguiContext>(first).unwrap();
+        ctx.get_mut().memory_mut(|memory| {
+            memory.data.insert_temp(field_id, 1_u32);
+            memory.request_focus(field_id);
+        });
+        app.update();
+
+        let memory_state = |app: &mut App, window| {
+            let mut ctx = app.world.get_mut::<EguiContext>(window).unwrap();
+            ctx.get_mut().memory_mut(|memory| {
+                (
+                    memory.has_focus(field_id),
+                    memory.data.get_temp::<u32>(field_id),
+                )
+            })
+        };
+        assert_eq!(memory_state(&mut app, first), (true, Some(1)));
+        assert_eq!(memory_state(&mut app, second), (false, None));
+    }
+
+    #[test]
+    fn test_hovered_layer() {
+        let (mut app, entity) = test_app();
+        app.add_system(move |mut contexts: EguiContexts| {
+            egui::Window::new("Help")
+                .fixed_pos(egui::pos2(100.0, 100.0))
+                .show(contexts.ctx_for_window_mut(entity), |ui| ui.label("Help"));
+        });
+        let mut contexts = SystemState::<EguiContexts>::new(&mut app.world);
+        let mut hover = |app: &mut App, pos| {
+            app.world.send_event(EguiInputEvent {
+                window: entity,
+                event: egui::Event::PointerMoved(pos),
+            });
+            app.update();
+            contexts.get_mut(&mut app.world).hovered_layer(entity)
+        };
+
+        assert_eq!(
+            hover(&mut app, egui::pos2(110.0, 110.0)),
+            Some(egui::LayerId::new(
+                egui::Order::Middle,
+                egui::Id::new("Help")
+            ))
+        );
+        assert_eq!(
+            hover(&mut app, egui::pos2(500.0, 400.0)),
+            Some(egui::LayerId::background())
+        );
+    }
+
+    #[test]
+    fn test_modal_backdrop_blocks_pointer() {
+        #[derive(Resource)]
+        struct ShowModal(bool);
+
+        let (mut app, entity) = test_app();
+        app.insert_resource(ShowModal(false)).add_system(
+            move |mut contexts: EguiContexts, show_modal: Res<ShowModal>| {
+                let ctx = contexts.ctx_for_window_mut(entity);
+                egui::TopBottomPanel::top("hud").show(ctx, |ui| ui.label("HUD"));
+                if show_modal.0 {
+                    egui::Area::new("backdrop")
+                        .order(egui::Order::Foreground)
+                        .fixed_pos(egui::Pos2::ZERO)
+                        .show(ctx, |ui| {
+                            let screen_rect = ui.ctx().screen_rect();
+                            ui.painter().rect_filled(
+                                screen_rect,
+                                0.0,
+                                egui::Color32::from_black_alpha(128),
+                            );
+                            ui.allocate_rect(screen_rect, egui::Sense::click());
+                        });
+                }
+            },
+        );
+        let mut contexts = SystemState::<EguiContexts>::new(&mut app.world);
+        let wants_pointer = |app: &mut App| {
+            app.world.send_event(CursorMoved {
+                window: entity,
+                position: Vec2::new(320.0, 100.0),
+            });
+            app.update();
+            let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+            ctx.get_mut().wants_pointer_input()
+        };
+
+        assert!(!wants_pointer(&mut app));
+        assert!(!contexts
+            .get_mut(&mut app.world)
+            .is_pointer_over_area(entity, egui::pos2(320.0, 380.0)));
+
+        app.world.resource_mut::<ShowModal>().0 = true;
+        assert!(wants_pointer(&mut app));
+        assert!(contexts
+            .get_mut(&mut app.world)
+            .is_pointer_over_area(entity, egui::pos2(320.0, 380.0)));
+    }
+
+    #[test]
+    fn test_used_rect() {
+        #[derive(Resource)]
+        struct ShowPanel(bool);
+
+        let (mut app, entity) = test_app();
+        app.insert_resource(ShowPanel(false)).add_system(
+            move |mut contexts: EguiContexts, show_panel: Res<ShowPanel>| {
+                if show_panel.0 {
+                    egui::TopBottomPanel::top("top")
+                        .exact_height(40.0)
+                        .show(contexts.ctx_for_window_mut(entity), |_ui| {});
+                }
+            },
+        );
+        let mut coordinates = SystemState::<EguiCoordinates>::new(&mut app.world);
+        app.update();
+        assert_eq!(coordinates.get(&app.world).used_rect(entity), None);
+
+        app.world.resource_mut::<ShowPanel>().0 = true;
+        app.update();
+        // The panel also reserves the default item spacing (4 points) below itself.
+        assert_eq!(
+            coordinates.get(&app.world).used_rect(entity),
+            Some(Rect::new(0.0, 436.0, 640.0, 480.0))
+        );
+    }
+
+    #[test]
+    fn test_window_to_egui_pos() {
+        let window_size = WindowSize::new(800.0, 600.0, 2.0);
+        let viewport = EguiViewport(Rect::new(100.0, 50.0, 300.0, 250.0));
+        let mut settings = EguiSettings::default();
+
+        let pos = Vec2::new(150.0, 200.0);
+        assert_eq!(
+            window_to_egui_pos(&settings, window_size, None, pos),
+            egui::pos2(150.0, 100.0)
+        );
+        assert_eq!(
+            window_to_egui_pos(&settings, window_size, Some(&viewport), pos),
+            egui::pos2(50.0, 50.0)
+        );
+        settings.scale_factor = 2.0;
+        let egui_pos = window_to_egui_pos(&settings, window_size, Some(&viewport), pos);
+        assert_eq!(egui_pos, egui::pos2(25.0, 25.0));
+        assert_eq!(
+            egui_to_window_pos(&settings, window_size, Some(&viewport), egui_pos),
+            pos
+        );
+    }
+
+    #[test]
+    fn test_reference_resolution_scale_factor() {
+        let settings = EguiSettings {
+            scale_factor_mode: EguiScaleFactorMode::ReferenceResolution(Vec2::new(1920.0, 1080.0)),
+            ..Default::default()
+        };
+        let pixels_per_point = |window_size: WindowSize| {
+            window_size.scale_factor * settings.effective_scale_factor(window_size)
+        };
+        assert_eq!(pixels_per_point(WindowSize::new(1920.0, 1080.0, 1.0)), 1.0);
+        assert_eq!(pixels_per_point(WindowSize::new(3840.0, 2160.0, 2.0)), 2.0);
+        // The UI fits the narrower axis.
+        assert_eq!(pixels_per_point(WindowSize::new(960.0, 1080.0, 1.0)), 0.5);
+        assert_eq!(pixels_per_point(WindowSize::new(0.0, 0.0, 1.0)), 1.0);
+    }
+
+    #[test]
+    fn test_image_regions_share_texture() {
+        let mut user_textures = EguiUserTextures::default();
+        let image = Handle::<Image>::weak(HandleId::random::<Image>());
+        let sheet_size = Vec2::new(64.0, 32.0);
+        let (first_id, first_uv) = user_textures.add_image_region(
+            image.clone(),
+            Rect::new(0.0, 0.0, 16.0, 16.0),
+            sheet_size,
+        );
+        let (second_id, second_uv) = user_textures.add_image_region(
+            image.clone(),
+            Rect::new(48.0, 16.0, 64.0, 32.0),
+            sheet_size,
+        );
+        assert_eq!(first_id, second_id);
+        assert_eq!(user_textures.image_id(&image), Some(first_id));
+        assert_eq!(
+            first_uv,
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(0.25, 0.5))
+        );
+        assert_eq!(
+            second_uv,
+            egui::Rect::from_min_max(egui::pos2(0.75, 0.5), egui::pos2(1.0, 1.0))
+        );
+    }
+
     #[test]
     fn test_headless_mode() {
         App::new()
@@ -773,7 +3402,7 @@ mod tests {
                     .build()
                     .disable::<WinitPlugin>(),
             )
-            .add_plugin(EguiPlugin)
+            .add_plugin(EguiPlugin::default())
             .update();
     }
 }
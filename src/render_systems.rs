@@ -1,24 +1,31 @@
 use crate::{
-    egui_node::{EguiNode, EguiPipeline, EguiPipelineKey},
-    EguiContextQueryReadOnly, EguiManagedTextures, EguiSettings, EguiUserTextures, WindowSize,
+    egui_node::{self, EguiPipeline, EguiPipelineKey},
+    EguiBlendMode, EguiCaptureRequest, EguiContext, EguiContextQueryReadOnly, EguiGlobalTint,
+    EguiManagedTextures, EguiManagedTexturesPartialUpdates, EguiRenderOutput, EguiSettings,
+    EguiTextureOptions, EguiUserTextures, EguiViewport, WindowSize,
 };
 use bevy::{
     asset::HandleId,
     prelude::*,
     render::{
+        camera::{ExtractedCamera, NormalizedRenderTarget},
         render_asset::RenderAssets,
-        render_graph::RenderGraph,
         render_resource::{
-            BindGroup, BindGroupDescriptor, BindGroupEntry, BindingResource, BufferId,
-            CachedRenderPipelineId, DynamicUniformBuffer, PipelineCache, ShaderType,
-            SpecializedRenderPipelines,
+            AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindingResource, Buffer,
+            BufferId, CachedRenderPipelineId, DynamicUniformBuffer, Extent3d, FilterMode,
+            ImageCopyTexture, ImageDataLayout, MapMode, Origin3d, PipelineCache, Sampler,
+            SamplerDescriptor, SpecializedRenderPipelines, TextureAspect, TextureDimension,
         },
         renderer::{RenderDevice, RenderQueue},
         texture::Image,
         view::ExtractedWindows,
         Extract,
     },
-    utils::HashMap,
+    utils::{HashMap, HashSet},
+};
+use std::{
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
 };
 
 /// Extracted Egui settings.
@@ -41,6 +48,8 @@ pub struct ExtractedEguiTextures {
     pub egui_textures: HashMap<(Entity, u64), Handle<Image>>,
     /// Maps Bevy managed texture handles to Egui user texture ids.
     pub user_textures: HashMap<Handle<Image>, u64>,
+    /// Filtering of the user textures that don't use their own samplers.
+    pub user_texture_options: HashMap<u64, EguiTextureOptions>,
 }
 
 impl ExtractedEguiTextures {
@@ -59,36 +68,84 @@ impl ExtractedEguiTextures {
     }
 }
 
-/// Sets up the pipeline for newly created windows.
-pub fn setup_new_windows_render_system(
-    windows: Extract<Query<Entity, Added<Window>>>,
-    mut render_graph: ResMut<RenderGraph>,
-) {
-    for window in windows.iter() {
-        let egui_pass = format!("egui-{}-{}", window.index(), window.generation());
-
-        let new_node = EguiNode::new(window);
+/// Draw calls issued by the render node for each context during the last rendered frame.
+///
+/// Is shared between the main and the render worlds, see [`crate::EguiFrameStats`].
+#[derive(Resource, Clone, Default)]
+pub struct EguiDrawCalls(pub Arc<Mutex<HashMap<Entity, usize>>>);
 
-        render_graph.add_node(egui_pass.clone(), new_node);
+/// Captures of Egui contexts that have been encoded by the render node.
+#[derive(Resource, Default)]
+pub struct EguiPendingCaptures {
+    /// Textures copied to buffers, which are waiting to be read back.
+    pub captures: Mutex<Vec<EguiPendingCapture>>,
+    /// Window entities of the contexts being read back, their requests aren't extracted again
+    /// until the images are delivered.
+    pub in_flight: Mutex<HashSet<Entity>>,
+}
 
-        render_graph.add_node_edge(
-            bevy::render::main_graph::node::CAMERA_DRIVER,
-            egui_pass.to_string(),
-        );
-    }
+/// A captured Egui context waiting to be read back, see [`crate::EguiCaptureRequest`].
+pub struct EguiPendingCapture {
+    /// Window entity of the captured context.
+    pub window: Entity,
+    /// Buffer containing the rendered texture.
+    pub buffer: Buffer,
+    /// Size of the texture in pixels.
+    pub size: Extent3d,
+    /// Number of bytes in a buffer row, which is padded to satisfy the copy alignment.
+    pub padded_bytes_per_row: u32,
 }
 
-/// Extracts Egui context, render output, settings and application window sizes.
+/// Images of captured Egui contexts that have been read back, [`None`] if reading back failed.
+///
+/// Is shared between the main and the render worlds, see [`crate::EguiCaptured`].
+#[allow(clippy::type_complexity)]
+#[derive(Resource, Clone, Default)]
+pub struct EguiCapturedImages(pub Arc<Mutex<Vec<(Entity, Option<Image>)>>>);
+
+/// Extracts Egui context, render output, settings, application window sizes and viewports.
+#[allow(clippy::type_complexity)]
 pub fn extract_egui_render_data_system(
     mut commands: Commands,
     egui_settings: Extract<Res<EguiSettings>>,
     contexts: Extract<Query<EguiContextQueryReadOnly>>,
+    capture_requests: Extract<Query<Entity, (With<EguiCaptureRequest>, With<EguiContext>)>>,
+    new_capture_requests: Extract<Query<Entity, Added<EguiCaptureRequest>>>,
+    mut pending_captures: ResMut<EguiPendingCaptures>,
 ) {
+    // A request stays in flight until the main world removes it after receiving the image,
+    // unless it has been inserted again since.
+    let in_flight = pending_captures.in_flight.get_mut().unwrap();
+    in_flight.retain(|window| {
+        capture_requests.contains(*window) && !new_capture_requests.contains(*window)
+    });
+
     commands.insert_resource(ExtractedEguiSettings(egui_settings.clone()));
-    for context in contexts.iter() {
-        commands
-            .get_or_spawn(context.window_entity)
-            .insert((*context.window_size, context.render_output.clone()));
+    for context in contexts
+        .iter()
+        .filter(|context| context.disabled.is_none() && context.render_disabled.is_none())
+    {
+        let mut entity_commands = commands.get_or_spawn(context.window_entity);
+        entity_commands.insert((*context.window_size, context.render_output.clone()));
+        if let Some(viewport) = context.viewport {
+            entity_commands.insert(*viewport);
+        }
+        if let Some(render_order) = context.render_order {
+            entity_commands.insert(*render_order);
+        }
+        if let Some(blend_mode) = context.blend_mode {
+            entity_commands.insert(*blend_mode);
+        }
+        if let Some(global_tint) = context.global_tint {
+            entity_commands.insert(*global_tint);
+        }
+        // Requests that haven't been served yet are extracted every frame, so that they are
+        // retried until the render node can capture the context.
+        if capture_requests.contains(context.window_entity)
+            && !in_flight.contains(&context.window_entity)
+        {
+            entity_commands.insert(EguiCaptureRequest);
+        }
     }
 }
 
@@ -106,9 +163,89 @@ pub fn extract_egui_textures_system(
             })
             .collect(),
         user_textures: egui_user_textures.textures.clone(),
+        user_texture_options: egui_user_textures.texture_options.clone(),
     });
 }
 
+/// Extracted partial updates of Egui managed textures.
+#[derive(Resource, Default)]
+pub struct ExtractedEguiTexturePartialUpdates(pub Vec<ExtractedEguiTexturePartialUpdate>);
+
+/// Extracted partial update of an Egui managed texture.
+pub struct ExtractedEguiTexturePartialUpdate {
+    /// Bevy image handle of the updated texture.
+    pub handle: Handle<Image>,
+    /// Position of the top left corner of the updated region.
+    pub pos: [usize; 2],
+    /// Size of the updated region.
+    pub size: [usize; 2],
+    /// sRGBA pixels of the updated region.
+    pub pixels: Vec<u8>,
+}
+
+/// Extracts partial updates of Egui managed textures.
+pub fn extract_egui_texture_partial_updates_system(
+    mut commands: Commands,
+    egui_partial_updates: Extract<Res<EguiManagedTexturesPartialUpdates>>,
+) {
+    commands.insert_resource(ExtractedEguiTexturePartialUpdates(
+        egui_partial_updates
+            .iter()
+            .map(|update| ExtractedEguiTexturePartialUpdate {
+                handle: update.handle.clone_weak(),
+                pos: update.pos,
+                size: update.color_image.size,
+                pixels: egui_node::color_image_pixels(&update.color_image),
+            })
+            .collect(),
+    ));
+}
+
+/// Writes changed regions of Egui managed textures directly to the GPU textures.
+///
+/// Runs after the image assets are prepared, so that textures created during the same frame
+/// are already available.
+pub fn write_egui_texture_partial_updates_system(
+    partial_updates: Res<ExtractedEguiTexturePartialUpdates>,
+    gpu_images: Res<RenderAssets<Image>>,
+    render_queue: Res<RenderQueue>,
+) {
+    for update in &partial_updates.0 {
+        let Some(gpu_image) = gpu_images.get(&update.handle) else {
+            bevy::log::warn!(
+                "Partial update of a texture that hasn't been uploaded (handle: {:?})",
+                update.handle
+            );
+            continue;
+        };
+        let [x, y] = update.pos;
+        let [width, height] = update.size;
+        render_queue.write_texture(
+            ImageCopyTexture {
+                texture: &gpu_image.texture,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: x as u32,
+                    y: y as u32,
+                    z: 0,
+                },
+                aspect: TextureAspect::All,
+            },
+            &update.pixels,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * width as u32),
+                rows_per_image: NonZeroU32::new(height as u32),
+            },
+            Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
 /// Describes the transform buffer.
 #[derive(Resource, Default)]
 pub struct EguiTransforms {
@@ -120,14 +257,34 @@ pub struct EguiTransforms {
     pub bind_group: Option<(BufferId, BindGroup)>,
 }
 
-/// Scale and translation for rendering Egui shapes. Is needed to transform Egui coordinates from
-/// the screen space with the center at (0, 0) to the normalised viewport space.
-#[derive(ShaderType, Default)]
-pub struct EguiTransform {
-    /// Is affected by window size and [`EguiSettings::scale_factor`].
-    pub scale: Vec2,
-    /// Normally equals `Vec2::new(-1.0, 1.0)`.
-    pub translation: Vec2,
+pub use egui_transform::EguiTransform;
+
+// The `ShaderType` derive generates assertion functions that newer compilers report as unused.
+// They are emitted next to the struct rather than inside it, so an `allow` on the struct itself
+// doesn't cover them, and the struct gets a module of its own instead.
+#[allow(dead_code)]
+mod egui_transform {
+    #[cfg(doc)]
+    use crate::{EguiGlobalTint, EguiSettings};
+    use bevy::{
+        math::{Vec2, Vec4},
+        render::render_resource::ShaderType,
+    };
+
+    /// Scale and translation for rendering Egui shapes. Is needed to transform Egui coordinates
+    /// from the screen space with the center at (0, 0) to the normalised viewport space.
+    #[derive(ShaderType, Default)]
+    pub struct EguiTransform {
+        /// Is affected by window size and [`EguiSettings::scale_factor`].
+        pub scale: Vec2,
+        /// Normally equals `Vec2::new(-1.0, 1.0)`.
+        pub translation: Vec2,
+        /// Multiplier of colors for HDR targets, see [`EguiSettings::hdr_paper_white`].
+        pub hdr_scale: f32,
+        /// Premultiplied linear color that all the colors are multiplied by, see
+        /// [`EguiGlobalTint`].
+        pub tint: Vec4,
+    }
 }
 
 impl EguiTransform {
@@ -139,14 +296,24 @@ impl EguiTransform {
                 -2.0 / (window_size.height() / scale_factor),
             ),
             translation: Vec2::new(-1.0, 1.0),
+            hdr_scale: 1.0,
+            tint: Vec4::ONE,
         }
     }
 }
 
+/// Luminance of `1.0` in the scRGB color space, which is used by floating point targets.
+const SCRGB_WHITE_NITS: f32 = 80.0;
+
 /// Prepares Egui transforms.
 pub fn prepare_egui_transforms_system(
     mut egui_transforms: ResMut<EguiTransforms>,
-    window_sizes: Query<(Entity, &WindowSize)>,
+    window_sizes: Query<(
+        Entity,
+        &WindowSize,
+        Option<&EguiViewport>,
+        Option<&EguiGlobalTint>,
+    )>,
     egui_settings: Res<ExtractedEguiSettings>,
 
     render_device: Res<RenderDevice>,
@@ -157,11 +324,22 @@ pub fn prepare_egui_transforms_system(
     egui_transforms.buffer.clear();
     egui_transforms.offsets.clear();
 
-    for (window, size) in window_sizes.iter() {
-        let offset = egui_transforms.buffer.push(EguiTransform::from_window_size(
-            *size,
-            egui_settings.scale_factor as f32,
-        ));
+    for (window, size, viewport, global_tint) in window_sizes.iter() {
+        let mut transform =
+            EguiTransform::from_window_size(*size, egui_settings.effective_scale_factor(*size));
+        if let Some(viewport) = viewport {
+            let viewport_min = viewport.physical_rect(*size).min;
+            transform.translation += Vec2::new(
+                2.0 * viewport_min.x / size.physical_width,
+                -2.0 * viewport_min.y / size.physical_height,
+            );
+        }
+        transform.hdr_scale = egui_settings.hdr_paper_white / SCRGB_WHITE_NITS;
+        if let Some(global_tint) = global_tint {
+            let [r, g, b, a] = global_tint.as_linear_rgba_f32();
+            transform.tint = Vec4::new(r * a, g * a, b * a, a);
+        }
+        let offset = egui_transforms.buffer.push(transform);
         egui_transforms.offsets.insert(window, offset);
     }
 
@@ -198,11 +376,25 @@ pub fn queue_bind_groups_system(
     render_device: Res<RenderDevice>,
     gpu_images: Res<RenderAssets<Image>>,
     egui_pipeline: Res<EguiPipeline>,
+    egui_settings: Res<ExtractedEguiSettings>,
+    mut samplers: Local<HashMap<EguiTextureOptions, Sampler>>,
 ) {
     let bind_groups = egui_textures
         .handles()
         .filter_map(|(texture, handle_id)| {
             let gpu_image = gpu_images.get(&Handle::weak(handle_id))?;
+            let sampler = match texture {
+                EguiTextureId::User(id) => egui_textures.user_texture_options.get(&id),
+                // Egui always allocates the font atlas first.
+                EguiTextureId::Managed(_, 0) => Some(&egui_settings.font_atlas_options),
+                EguiTextureId::Managed(..) => None,
+            }
+            .map(|options| {
+                samplers
+                    .entry(*options)
+                    .or_insert_with(|| create_sampler(&render_device, *options))
+                    .clone()
+            });
             let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
                 label: None,
                 layout: &egui_pipeline.texture_bind_group_layout,
@@ -213,7 +405,9 @@ pub fn queue_bind_groups_system(
                     },
                     BindGroupEntry {
                         binding: 1,
-                        resource: BindingResource::Sampler(&gpu_image.sampler),
+                        resource: BindingResource::Sampler(
+                            sampler.as_ref().unwrap_or(&gpu_image.sampler),
+                        ),
                     },
                 ],
             });
@@ -224,29 +418,122 @@ pub fn queue_bind_groups_system(
     commands.insert_resource(EguiTextureBindGroups(bind_groups))
 }
 
+fn create_sampler(render_device: &RenderDevice, options: EguiTextureOptions) -> Sampler {
+    let filter_mode = |filter| match filter {
+        egui::TextureFilter::Nearest => FilterMode::Nearest,
+        egui::TextureFilter::Linear => FilterMode::Linear,
+    };
+    render_device.create_sampler(&SamplerDescriptor {
+        label: Some("egui user texture sampler"),
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        mag_filter: filter_mode(options.magnification),
+        min_filter: filter_mode(options.minification),
+        ..Default::default()
+    })
+}
+
 /// Cached Pipeline IDs for the specialized `EguiPipeline`s
 #[derive(Resource)]
 pub struct EguiPipelines(pub HashMap<Entity, CachedRenderPipelineId>);
 
-/// Queue [`EguiPipeline`]s specialized on each window's swap chain texture format.
+/// Cached Pipeline ID of the `EguiPipeline` specialized for capture textures.
+#[derive(Resource)]
+pub struct EguiCapturePipeline(pub CachedRenderPipelineId);
+
+/// Queue [`EguiPipeline`]s specialized on each window's swap chain texture format, and on
+/// the texture formats of the images that camera contexts are drawn into.
+#[allow(clippy::too_many_arguments)]
 pub fn queue_pipelines_system(
     mut commands: Commands,
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<EguiPipeline>>,
     egui_pipeline: Res<EguiPipeline>,
+    egui_settings: Res<ExtractedEguiSettings>,
     windows: Res<ExtractedWindows>,
+    cameras: Query<(Entity, &ExtractedCamera), With<EguiRenderOutput>>,
+    blend_modes: Query<&EguiBlendMode>,
+    gpu_images: Res<RenderAssets<Image>>,
 ) {
-    let pipelines = windows
+    let window_formats = windows
         .iter()
-        .filter_map(|(window_id, window)| {
+        .filter_map(|(window_id, window)| Some((*window_id, window.swap_chain_texture_format?)));
+    let image_formats = cameras.iter().filter_map(|(entity, camera)| {
+        let Some(NormalizedRenderTarget::Image(image)) = &camera.target else {
+            return None;
+        };
+        Some((entity, gpu_images.get(image)?.texture_format))
+    });
+    let window_pipelines = window_formats
+        .chain(image_formats)
+        .map(|(target, texture_format)| {
             let key = EguiPipelineKey {
-                texture_format: window.swap_chain_texture_format?,
+                texture_format,
+                custom_shader: egui_settings.custom_shader.clone(),
+                blend_mode: blend_modes.get(target).copied().unwrap_or_default(),
             };
             let pipeline_id = pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
 
-            Some((*window_id, pipeline_id))
+            (target, pipeline_id)
         })
         .collect();
 
-    commands.insert_resource(EguiPipelines(pipelines));
+    commands.insert_resource(EguiPipelines(window_pipelines));
+
+    let capture_key = EguiPipelineKey {
+        texture_format: egui_node::EGUI_CAPTURE_FORMAT,
+        custom_shader: egui_settings.custom_shader.clone(),
+        blend_mode: EguiBlendMode::Premultiplied,
+    };
+    commands.insert_resource(EguiCapturePipeline(pipelines.specialize(
+        &pipeline_cache,
+        &egui_pipeline,
+        capture_key,
+    )));
+}
+
+/// Starts reading back the textures of captured Egui contexts.
+///
+/// Runs after the render graph commands are submitted. The buffers are mapped asynchronously,
+/// so that the render thread isn't blocked, and the images are passed to the main world once
+/// the mapping is done.
+pub fn map_egui_captures_system(
+    pending_captures: Res<EguiPendingCaptures>,
+    captured_images: Res<EguiCapturedImages>,
+) {
+    for capture in pending_captures.captures.lock().unwrap().drain(..) {
+        let captured_images = captured_images.0.clone();
+        let buffer = capture.buffer.clone();
+        capture
+            .buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                if let Err(err) = result {
+                    bevy::log::error!("Failed to read back a captured Egui context: {err}");
+                    // The failure is still reported, so that the request is removed.
+                    captured_images.lock().unwrap().push((capture.window, None));
+                    return;
+                }
+                let row_size = 4 * capture.size.width as usize;
+                let data = {
+                    let padded_data = buffer.slice(..).get_mapped_range();
+                    padded_data
+                        .chunks(capture.padded_bytes_per_row as usize)
+                        .flat_map(|row| &row[..row_size])
+                        .copied()
+                        .collect()
+                };
+                buffer.unmap();
+                let image = Image::new(
+                    capture.size,
+                    TextureDimension::D2,
+                    data,
+                    egui_node::EGUI_CAPTURE_FORMAT,
+                );
+                captured_images
+                    .lock()
+                    .unwrap()
+                    .push((capture.window, Some(image)));
+            });
+    }
 }
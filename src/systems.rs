@@ -1,22 +1,41 @@
 use crate::{
-    EguiContext, EguiContextQuery, EguiInput, EguiMousePosition, EguiSettings, WindowSize,
+    render_systems::EguiDrawCalls, top_left_window_to_egui_pos, window_to_egui_pos, EguiContext,
+    EguiContextFrameStats, EguiContextQuery, EguiContextQueryItem, EguiCopyEvent, EguiDisabled,
+    EguiDragAndDrop, EguiEventFilter, EguiFocusedWindow, EguiFrameStats, EguiFullOutput,
+    EguiGamepadBindings, EguiImeRequest, EguiInput, EguiInputDebugOverlay, EguiInputEvent,
+    EguiMousePosition, EguiNavigationAction, EguiOutputEvent, EguiRepaint, EguiRepaintRequested,
+    EguiScrollCapture, EguiScrollPolicy, EguiScrollSmoothing, EguiSettings, EguiTargetCamera,
+    EguiTimeSource, EguiViewport, EguiZoomFactor, WindowSize,
 };
 #[cfg(feature = "open_url")]
 use bevy::log;
+#[cfg(feature = "reactive_repaint")]
+use bevy::winit::{UpdateMode, WinitSettings};
 use bevy::{
     ecs::{
-        event::EventWriter,
+        event::Events,
+        query::{With, Without},
+        removal_detection::RemovedComponents,
         system::{Local, Res, ResMut, SystemParam},
     },
     input::{
+        gamepad::{GamepadAxis, GamepadAxisType, GamepadButton, Gamepads},
         keyboard::{KeyCode, KeyboardInput},
         mouse::{MouseButton, MouseButtonInput, MouseScrollUnit, MouseWheel},
-        ButtonState, Input,
+        touch::{ForceTouch, TouchInput, TouchPhase},
+        Axis, ButtonState, Input,
     },
-    prelude::{Entity, EventReader, Query, Time},
+    math::{Rect, Vec2},
+    prelude::{Commands, DetectChanges, Entity, EventReader, EventWriter, Query, Time},
+    render::{
+        camera::{Camera, NormalizedRenderTarget},
+        renderer::RenderDevice,
+    },
+    time::fixed_timestep::FixedTime,
+    utils::{Duration, HashSet, Instant},
     window::{
-        CursorEntered, CursorLeft, CursorMoved, ReceivedCharacter, RequestRedraw, WindowCreated,
-        WindowFocused,
+        CursorEntered, CursorGrabMode, CursorLeft, CursorMoved, FileDragAndDrop, Ime,
+        PrimaryWindow, ReceivedCharacter, RequestRedraw, WindowCreated, WindowFocused,
     },
 };
 use std::marker::PhantomData;
@@ -33,6 +52,9 @@ pub struct InputEvents<'w, 's> {
     pub ev_keyboard_input: EventReader<'w, 's, KeyboardInput>,
     pub ev_window_focused: EventReader<'w, 's, WindowFocused>,
     pub ev_window_created: EventReader<'w, 's, WindowCreated>,
+    pub ev_file_drag_and_drop: EventReader<'w, 's, FileDragAndDrop>,
+    pub ev_touch: EventReader<'w, 's, TouchInput>,
+    pub ev_ime: EventReader<'w, 's, Ime>,
 }
 
 impl<'w, 's> InputEvents<'w, 's> {
@@ -47,6 +69,9 @@ impl<'w, 's> InputEvents<'w, 's> {
         self.ev_keyboard_input.iter().last();
         self.ev_window_focused.iter().last();
         self.ev_window_created.iter().last();
+        self.ev_file_drag_and_drop.iter().last();
+        self.ev_touch.iter().last();
+        self.ev_ime.iter().last();
     }
 }
 
@@ -63,12 +88,60 @@ pub struct InputResources<'w, 's> {
 #[allow(missing_docs)]
 #[derive(SystemParam)]
 pub struct ContextSystemParams<'w, 's> {
-    pub focused_window: Local<'s, Option<Entity>>,
+    pub focused_window: ResMut<'w, EguiFocusedWindow>,
+    pub pending_keyboard_events: Local<'s, PendingKeyboardEvents>,
+    /// Keys that were held when a window lost focus, their releases may never be reported.
+    pub stale_keys: Local<'s, HashSet<KeyCode>>,
+    /// Smoothed scrolling that hasn't been passed to Egui yet.
+    pub pending_scroll: Local<'s, Option<(Entity, egui::Vec2)>>,
+    pub scroll_capture: ResMut<'w, EguiScrollCapture>,
     pub contexts: Query<'w, 's, EguiContextQuery>,
+    pub primary_window: Query<'w, 's, Entity, With<PrimaryWindow>>,
+    pub removed_disabled: RemovedComponents<'w, 's, EguiDisabled>,
     #[system_param(ignore)]
     _marker: PhantomData<&'s ()>,
 }
 
+/// Keyboard events that arrived while no window was focused.
+///
+/// Focus changes can be reported a frame after the input that caused them, so instead of
+/// discarding such events, they are held for a couple of frames and delivered to the next
+/// focused context.
+#[derive(Default)]
+pub struct PendingKeyboardEvents {
+    events: Vec<egui::Event>,
+    frames: u32,
+}
+
+impl PendingKeyboardEvents {
+    /// Maximum number of held events, the rest are discarded.
+    const MAX_EVENTS: usize = 32;
+    /// Number of frames the events are held for.
+    const MAX_FRAMES: u32 = 2;
+
+    fn push(&mut self, events: Vec<egui::Event>) {
+        if !self.events.is_empty() {
+            self.frames += 1;
+            if self.frames > Self::MAX_FRAMES {
+                self.events.clear();
+            }
+        }
+        if self.events.is_empty() {
+            self.frames = 0;
+        }
+        self.events.extend(events);
+        self.events.truncate(Self::MAX_EVENTS);
+    }
+
+    fn take(&mut self) -> Vec<egui::Event> {
+        self.frames = 0;
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Time constant of smoothed scrolling in seconds, see [`EguiSettings::scroll_smoothing`].
+const SCROLL_SMOOTHING_TIME: f32 = 0.05;
+
 /// Processes Bevy input and feeds it to Egui.
 pub fn process_input_system(
     mut input_events: InputEvents,
@@ -76,30 +149,77 @@ pub fn process_input_system(
     mut context_params: ContextSystemParams,
     egui_settings: Res<EguiSettings>,
     mut egui_mouse_position: ResMut<EguiMousePosition>,
+    mut pointer_touch_id: Local<Option<u64>>,
     time: Res<Time>,
 ) {
+    // Windows that gained focus during this frame, in the order of the events.
     // This is a workaround for Windows. For some reason, `WindowFocused` event isn't fired
     // when a window is created.
-    if let Some(event) = input_events.ev_window_created.iter().last() {
-        *context_params.focused_window = Some(event.window);
-    }
+    let mut focus_candidates: Vec<Entity> = input_events
+        .ev_window_created
+        .iter()
+        .map(|event| event.window)
+        .collect();
 
     for event in input_events.ev_window_focused.iter() {
-        *context_params.focused_window = if event.focused {
-            Some(event.window)
+        focus_candidates.retain(|&window| window != event.window);
+        if event.focused {
+            focus_candidates.push(event.window);
         } else {
-            None
-        };
+            **context_params.focused_window = None;
+        }
+
+        if !event.focused {
+            // Key releases aren't reported to unfocused windows, so the keys that are held now
+            // are ignored until they are pressed again.
+            context_params
+                .stale_keys
+                .extend(input_resources.keyboard_input.get_pressed().copied());
+        }
+
+        // Lets Egui pause the text cursor blinking while the window is in the background.
+        if let Ok(mut context) = context_params.contexts.get_mut(event.window) {
+            context.egui_input.has_focus = event.focused;
+            if !event.focused {
+                context.egui_input.modifiers = egui::Modifiers::default();
+                let keys_down = context.ctx.get_mut().input(|i| i.keys_down.clone());
+                context
+                    .egui_input
+                    .events
+                    .extend(keys_down.into_iter().map(|key| egui::Event::Key {
+                        key,
+                        pressed: false,
+                        repeat: false,
+                        modifiers: egui::Modifiers::default(),
+                    }));
+            }
+        }
+    }
+    // Several windows can claim focus in the same frame (when they are created or during
+    // transitions), the highest priority wins, ties go to the latest event.
+    if let Some(window) = focus_candidates.into_iter().max_by_key(|&window| {
+        context_params
+            .contexts
+            .get(window)
+            .ok()
+            .and_then(|context| context.focus_priority.copied())
+            .unwrap_or_default()
+    }) {
+        **context_params.focused_window = Some(window);
     }
 
-    let shift = input_resources.keyboard_input.pressed(KeyCode::LShift)
-        || input_resources.keyboard_input.pressed(KeyCode::RShift);
-    let ctrl = input_resources.keyboard_input.pressed(KeyCode::LControl)
-        || input_resources.keyboard_input.pressed(KeyCode::RControl);
-    let alt = input_resources.keyboard_input.pressed(KeyCode::LAlt)
-        || input_resources.keyboard_input.pressed(KeyCode::RAlt);
-    let win = input_resources.keyboard_input.pressed(KeyCode::LWin)
-        || input_resources.keyboard_input.pressed(KeyCode::RWin);
+    context_params.stale_keys.retain(|key| {
+        input_resources.keyboard_input.pressed(*key)
+            && !input_resources.keyboard_input.just_pressed(*key)
+    });
+
+    let pressed = |key| {
+        input_resources.keyboard_input.pressed(key) && !context_params.stale_keys.contains(&key)
+    };
+    let shift = pressed(KeyCode::LShift) || pressed(KeyCode::RShift);
+    let ctrl = pressed(KeyCode::LControl) || pressed(KeyCode::RControl);
+    let alt = pressed(KeyCode::LAlt) || pressed(KeyCode::RAlt);
+    let win = pressed(KeyCode::LWin) || pressed(KeyCode::RWin);
 
     let mac_cmd = if cfg!(target_os = "macos") {
         win
@@ -137,36 +257,101 @@ pub fn process_input_system(
             None
         };
 
-    if let Some(cursor_moved) = input_events.ev_cursor.iter().last() {
+    // Egui only needs the latest position, so high-frequency mouse motion is coalesced into
+    // a single event per frame unless all the positions are requested.
+    let cursor_moved_events: Vec<&CursorMoved> = if egui_settings.coalesce_pointer_moves {
+        input_events.ev_cursor.iter().last().into_iter().collect()
+    } else {
+        input_events.ev_cursor.iter().collect()
+    };
+    let primary_window = context_params.primary_window.get_single().ok();
+    for cursor_moved in cursor_moved_events {
         // If we've left the window, it's unlikely that we've moved the cursor back to the same
         // window this exact frame, so we are safe to ignore all `CursorMoved` events for the window
         // that has been left.
-        if cursor_left_window != Some(cursor_moved.window) {
-            let scale_factor = egui_settings.scale_factor as f32;
-            let mut mouse_position: (f32, f32) = (cursor_moved.position / scale_factor).into();
-            let mut context = context_params
-                .contexts
-                .get_mut(cursor_moved.window)
-                .unwrap();
-            mouse_position.1 = context.window_size.height() / scale_factor - mouse_position.1;
-            egui_mouse_position.0 = Some((cursor_moved.window, mouse_position.into()));
-            context
-                .egui_input
-                .events
-                .push(egui::Event::PointerMoved(egui::pos2(
-                    mouse_position.0,
-                    mouse_position.1,
-                )));
+        if cursor_left_window == Some(cursor_moved.window) {
+            continue;
+        }
+
+        // Besides the window's own context, cameras that render into the window can have
+        // contexts restricted to their viewports. The pointer is passed to the context that keeps
+        // a button pressed, or to the topmost one under the pointer.
+        let mut hovered_context = None;
+        for mut context in context_params.contexts.iter_mut() {
+            if context_target_window(&context, primary_window) != Some(cursor_moved.window)
+                || !context.input_enabled.copied().unwrap_or_default().pointer
+            {
+                continue;
+            }
+            let mouse_position = window_to_egui_pos(
+                &egui_settings,
+                *context.window_size,
+                context.viewport,
+                cursor_moved.position,
+            );
+            // `Option::is_none_or` requires a newer compiler than the one supported by Bevy 0.10.
+            #[allow(clippy::unnecessary_map_or)]
+            let inside_viewport = context.viewport.map_or(true, |viewport| {
+                let pixels_per_point = context.window_size.scale_factor
+                    * egui_settings.effective_scale_factor(*context.window_size);
+                let viewport_size =
+                    viewport.physical_rect(*context.window_size).size() / pixels_per_point;
+                (0.0..=viewport_size.x).contains(&mouse_position.x)
+                    && (0.0..=viewport_size.y).contains(&mouse_position.y)
+            });
+            // Keep passing the pointer to Egui while dragging outside of the viewport, so that
+            // it receives the button release.
+            let pointer_down = context
+                .ctx
+                .get_mut()
+                .input(|input| input.pointer.any_down());
+            if !inside_viewport && !pointer_down {
+                continue;
+            }
+
+            let order = (
+                pointer_down,
+                context.render_order.copied().unwrap_or_default(),
+                context.window_entity,
+            );
+            #[allow(clippy::unnecessary_map_or)]
+            if hovered_context.map_or(true, |(hovered_order, _, _)| order > hovered_order) {
+                hovered_context = Some((order, context.window_entity, mouse_position));
+            }
+        }
+
+        let prev_context = match hovered_context {
+            Some((_, context_entity, mouse_position)) => {
+                if let Ok(mut context) = context_params.contexts.get_mut(context_entity) {
+                    context
+                        .egui_input
+                        .events
+                        .push(egui::Event::PointerMoved(mouse_position));
+                }
+                egui_mouse_position
+                    .replace((context_entity, mouse_position.to_vec2()))
+                    .filter(|(prev_context, _)| *prev_context != context_entity)
+            }
+            None => egui_mouse_position.take(),
+        };
+        if let Some((prev_context, _)) = prev_context {
+            if let Ok(mut context) = context_params.contexts.get_mut(prev_context) {
+                context.egui_input.events.push(egui::Event::PointerGone);
+            }
         }
     }
 
     // If we pressed a button, started dragging a cursor inside a window and released
     // the button when being outside, some platforms will fire `CursorLeft` again together
     // with `MouseButtonInput` - this is why we also take `prev_mouse_position` into account.
+    context_params.scroll_capture.0 = None;
     if let Some((window_id, position)) = egui_mouse_position.or(prev_mouse_position) {
-        if let Ok(mut context) = context_params.contexts.get_mut(window_id) {
-            let events = &mut context.egui_input.events;
-
+        let context = context_params
+            .contexts
+            .get_mut(window_id)
+            .ok()
+            .filter(|context| context.input_enabled.copied().unwrap_or_default().pointer);
+        if let Some(mut context) = context {
             for mouse_button_event in input_events.ev_mouse_button_input.iter() {
                 let button = match mouse_button_event.button {
                     MouseButton::Left => Some(egui::PointerButton::Primary),
@@ -179,7 +364,7 @@ pub fn process_input_system(
                     ButtonState::Released => false,
                 };
                 if let Some(button) = button {
-                    events.push(egui::Event::PointerButton {
+                    context.egui_input.events.push(egui::Event::PointerButton {
                         pos: position.to_pos2(),
                         button,
                         pressed,
@@ -188,21 +373,57 @@ pub fn process_input_system(
                 }
             }
 
-            for event in input_events.ev_mouse_wheel.iter() {
+            let pixels_per_point = context.window_size.scale_factor
+                * egui_settings.effective_scale_factor(*context.window_size);
+            let consume_scroll = match egui_settings.scroll_policy {
+                EguiScrollPolicy::Always => true,
+                EguiScrollPolicy::WhenWantsPointer => context.ctx.get_mut().wants_pointer_input(),
+            };
+            let events = &mut context.egui_input.events;
+
+            // Scrolling that isn't consumed is left to the app.
+            for event in input_events
+                .ev_mouse_wheel
+                .iter()
+                .filter(|_| consume_scroll)
+            {
+                context_params.scroll_capture.0 = Some(window_id);
+                let smooth = match egui_settings.scroll_smoothing {
+                    EguiScrollSmoothing::Disabled => false,
+                    EguiScrollSmoothing::Lines => event.unit == MouseScrollUnit::Line,
+                    EguiScrollSmoothing::All => true,
+                };
                 let mut delta = egui::vec2(event.x, event.y);
-                if let MouseScrollUnit::Line = event.unit {
-                    // https://github.com/emilk/egui/blob/a689b623a669d54ea85708a8c748eb07e23754b0/egui-winit/src/lib.rs#L449
-                    delta *= 50.0;
+                match event.unit {
+                    MouseScrollUnit::Line => {
+                        // https://github.com/emilk/egui/blob/a689b623a669d54ea85708a8c748eb07e23754b0/egui-winit/src/lib.rs#L449
+                        delta *= egui_settings.scroll_line_height;
+                    }
+                    MouseScrollUnit::Pixel => {
+                        delta = delta / pixels_per_point;
+                    }
+                }
+                if egui_settings.invert_scroll {
+                    delta = -delta;
                 }
 
                 if ctrl || mac_cmd {
                     // Treat as zoom instead.
                     let factor = (delta.y / 200.0).exp();
                     events.push(egui::Event::Zoom(factor));
-                } else if shift {
+                    continue;
+                }
+                if shift {
                     // Treat as horizontal scrolling.
                     // Note: Mac already fires horizontal scroll events when shift is down.
-                    events.push(egui::Event::Scroll(egui::vec2(delta.x + delta.y, 0.0)));
+                    delta = egui::vec2(delta.x + delta.y, 0.0);
+                }
+                if smooth {
+                    let pending_scroll = match *context_params.pending_scroll {
+                        Some((window, pending_scroll)) if window == window_id => pending_scroll,
+                        _ => egui::Vec2::ZERO,
+                    };
+                    *context_params.pending_scroll = Some((window_id, pending_scroll + delta));
                 } else {
                     events.push(egui::Event::Scroll(delta));
                 }
@@ -210,70 +431,290 @@ pub fn process_input_system(
         }
     }
 
+    // Passes a part of the smoothed scrolling that decays exponentially over time.
+    if let Some((window_id, pending_scroll)) = *context_params.pending_scroll {
+        let t = 1.0 - (-time.raw_delta_seconds() / SCROLL_SMOOTHING_TIME).exp();
+        let mut delta = pending_scroll * t;
+        if (pending_scroll - delta).length() < 1.0 {
+            delta = pending_scroll;
+        }
+        *context_params.pending_scroll =
+            (delta != pending_scroll).then_some((window_id, pending_scroll - delta));
+        if let Ok(mut context) = context_params.contexts.get_mut(window_id) {
+            context.egui_input.events.push(egui::Event::Scroll(delta));
+            if context_params.pending_scroll.is_some() {
+                context.ctx.get_mut().request_repaint();
+            }
+        }
+    }
+
+    // Clears hover states when the cursor leaves a window (after passing the button releases
+    // that may come together with it). The position is restored by the next `CursorMoved` event
+    // once the cursor enters the window again.
+    if let Some(window_id) =
+        cursor_left_window.filter(|window| cursor_entered_window != Some(*window))
+    {
+        if let Ok(mut context) = context_params.contexts.get_mut(window_id) {
+            context.egui_input.events.push(egui::Event::PointerGone);
+        }
+    }
+
+    // Text is taken from the characters reported by the OS, which respect the keyboard layout,
+    // while key codes are only used for `egui::Event::Key`.
     if !command || cfg!(target_os = "windows") && ctrl && alt {
         for event in input_events.ev_received_character.iter() {
-            if !event.char.is_control() {
-                let mut context = context_params.contexts.get_mut(event.window).unwrap();
-                context
-                    .egui_input
-                    .events
-                    .push(egui::Event::Text(event.char.to_string()));
+            if !is_printable_char(event.char) {
+                continue;
             }
+            let Ok(mut context) = context_params.contexts.get_mut(event.window) else {
+                continue;
+            };
+            if !context.input_enabled.copied().unwrap_or_default().keyboard {
+                continue;
+            }
+            if egui_settings.text_input_requires_focus
+                && !context.ctx.get_mut().wants_keyboard_input()
+            {
+                continue;
+            }
+            context
+                .egui_input
+                .events
+                .push(egui::Event::Text(event.char.to_string()));
         }
     }
 
-    if let Some(mut focused_input) = context_params
-        .focused_window
-        .as_ref()
-        .and_then(|window_id| {
-            if let Ok(context) = context_params.contexts.get_mut(*window_id) {
-                Some(context.egui_input)
-            } else {
-                None
+    // Is only reported by native platforms while the window has IME enabled, see below.
+    for event in input_events.ev_ime.iter() {
+        let (window, egui_event) = match event {
+            Ime::Enabled { window } => (*window, egui::Event::CompositionStart),
+            Ime::Preedit { window, value, .. } => {
+                (*window, egui::Event::CompositionUpdate(value.clone()))
             }
-        })
-    {
-        for ev in input_events.ev_keyboard_input.iter() {
-            if let Some(key) = ev.key_code.and_then(bevy_to_egui_key) {
-                let pressed = match ev.state {
-                    ButtonState::Pressed => true,
-                    ButtonState::Released => false,
-                };
-                let egui_event = egui::Event::Key {
-                    key,
-                    pressed,
-                    repeat: false,
-                    modifiers,
-                };
-                focused_input.events.push(egui_event);
-
-                // We also check that it's an `ButtonState::Pressed` event, as we don't want to
-                // copy, cut or paste on the key release.
-                #[cfg(feature = "manage_clipboard")]
-                if command && pressed {
-                    match key {
-                        egui::Key::C => {
-                            focused_input.events.push(egui::Event::Copy);
-                        }
-                        egui::Key::X => {
-                            focused_input.events.push(egui::Event::Cut);
-                        }
-                        egui::Key::V => {
-                            if let Some(contents) = input_resources.egui_clipboard.get_contents() {
-                                focused_input.events.push(egui::Event::Text(contents))
-                            }
+            Ime::Commit { window, value } => (*window, egui::Event::CompositionEnd(value.clone())),
+            Ime::Disabled { .. } => continue,
+        };
+        if let Ok(mut context) = context_params.contexts.get_mut(window) {
+            if context.input_enabled.copied().unwrap_or_default().keyboard {
+                context.egui_input.events.push(egui_event);
+            }
+        }
+    }
+
+    let mut keyboard_events = Vec::new();
+    for ev in input_events.ev_keyboard_input.iter() {
+        if let Some(key) = ev.key_code.and_then(bevy_to_egui_key) {
+            let pressed = match ev.state {
+                ButtonState::Pressed => true,
+                ButtonState::Released => false,
+            };
+            // Bevy doesn't report key repeats, but a repeated press of a held key doesn't
+            // make it "just pressed". Releases are never repeated.
+            let repeat = pressed
+                && ev
+                    .key_code
+                    .is_some_and(|key_code| !input_resources.keyboard_input.just_pressed(key_code));
+            let egui_event = egui::Event::Key {
+                key,
+                pressed,
+                repeat,
+                modifiers,
+            };
+            keyboard_events.push(egui_event);
+
+            // We also check that it's an `ButtonState::Pressed` event, as we don't want to
+            // copy, cut or paste on the key release.
+            #[cfg(feature = "manage_clipboard")]
+            if command && pressed {
+                match key {
+                    egui::Key::C => {
+                        keyboard_events.push(egui::Event::Copy);
+                    }
+                    egui::Key::X => {
+                        keyboard_events.push(egui::Event::Cut);
+                    }
+                    egui::Key::V => {
+                        if let Some(contents) = input_resources.egui_clipboard.get_contents() {
+                            keyboard_events.push(egui::Event::Paste(contents))
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
         }
+    }
+
+    match **context_params.focused_window {
+        Some(window_id) => {
+            let pending_events = context_params.pending_keyboard_events.take();
+            if let Ok(mut context) = context_params.contexts.get_mut(window_id) {
+                if context.input_enabled.copied().unwrap_or_default().keyboard {
+                    let pass_tab = !egui_settings.tab_requires_focus
+                        || context.ctx.get_mut().wants_keyboard_input();
+                    let is_passed = |event: &egui::Event| {
+                        pass_tab
+                            || !matches!(
+                                event,
+                                egui::Event::Key {
+                                    key: egui::Key::Tab,
+                                    ..
+                                }
+                            )
+                    };
+                    let focused_input = context.egui_input.into_inner();
+                    focused_input
+                        .events
+                        .extend(pending_events.into_iter().filter(is_passed));
+                    focused_input
+                        .events
+                        .extend(keyboard_events.into_iter().filter(is_passed));
+                    focused_input.modifiers = modifiers;
+                }
+            }
+        }
+        None => context_params.pending_keyboard_events.push(keyboard_events),
+    }
+
+    // Bevy doesn't tell which window touches belong to, so they're passed to the focused one.
+    let touch_context = context_params
+        .focused_window
+        .0
+        .and_then(|window| context_params.contexts.get_mut(window).ok())
+        .filter(|context| context.input_enabled.copied().unwrap_or_default().pointer);
+    if let Some(mut context) = touch_context {
+        for event in input_events.ev_touch.iter() {
+            // Touch positions have the origin at the top left corner of the window.
+            let pos = top_left_window_to_egui_pos(
+                &egui_settings,
+                *context.window_size,
+                context.viewport,
+                event.position,
+            );
+            let phase = match event.phase {
+                TouchPhase::Started => egui::TouchPhase::Start,
+                TouchPhase::Moved => egui::TouchPhase::Move,
+                TouchPhase::Ended => egui::TouchPhase::End,
+                TouchPhase::Cancelled => egui::TouchPhase::Cancel,
+            };
 
-        focused_input.modifiers = modifiers;
+            if egui_settings.touch_gestures {
+                let force = match event.force {
+                    Some(ForceTouch::Calibrated {
+                        force,
+                        max_possible_force,
+                        ..
+                    }) => (force / max_possible_force) as f32,
+                    Some(ForceTouch::Normalized(force)) => force as f32,
+                    None => 0.0,
+                };
+                context.egui_input.events.push(egui::Event::Touch {
+                    device_id: egui::TouchDeviceId(0),
+                    id: egui::TouchId(event.id),
+                    phase,
+                    pos,
+                    force,
+                });
+            }
+
+            // The first touch emulates the pointer, so that widgets can be clicked and dragged.
+            if pointer_touch_id.is_none() && phase == egui::TouchPhase::Start {
+                *pointer_touch_id = Some(event.id);
+            }
+            if *pointer_touch_id != Some(event.id) {
+                continue;
+            }
+            let events = &mut context.egui_input.events;
+            match phase {
+                egui::TouchPhase::Start => {
+                    events.push(egui::Event::PointerMoved(pos));
+                    events.push(egui::Event::PointerButton {
+                        pos,
+                        button: egui::PointerButton::Primary,
+                        pressed: true,
+                        modifiers,
+                    });
+                }
+                egui::TouchPhase::Move => {
+                    events.push(egui::Event::PointerMoved(pos));
+                }
+                egui::TouchPhase::End | egui::TouchPhase::Cancel => {
+                    *pointer_touch_id = None;
+                    events.push(egui::Event::PointerButton {
+                        pos,
+                        button: egui::PointerButton::Primary,
+                        pressed: false,
+                        modifiers,
+                    });
+                    events.push(egui::Event::PointerGone);
+                }
+            }
+        }
+    }
+
+    for event in input_events.ev_file_drag_and_drop.iter() {
+        match event {
+            FileDragAndDrop::HoveredFile { window, path_buf } => {
+                if let Ok(mut context) = context_params.contexts.get_mut(*window) {
+                    context.egui_input.hovered_files.push(egui::HoveredFile {
+                        path: Some(path_buf.clone()),
+                        ..Default::default()
+                    });
+                }
+            }
+            FileDragAndDrop::HoveredFileCancelled { window } => {
+                if let Ok(mut context) = context_params.contexts.get_mut(*window) {
+                    context.egui_input.hovered_files.clear();
+                }
+            }
+            FileDragAndDrop::DroppedFile { window, path_buf } => {
+                if let Ok(mut context) = context_params.contexts.get_mut(*window) {
+                    context.egui_input.hovered_files.clear();
+                    context.egui_input.dropped_files.push(egui::DroppedFile {
+                        path: Some(path_buf.clone()),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    // Disabled contexts don't run frames, so their input would pile up.
+    for mut context in context_params.contexts.iter_mut() {
+        if context.disabled.is_some() {
+            let egui_input = &mut context.egui_input;
+            egui_input.events.clear();
+            egui_input.hovered_files.clear();
+            egui_input.dropped_files.clear();
+        }
+    }
+    // Buttons and keys could be released while a context was disabled.
+    for window in context_params.removed_disabled.iter() {
+        if let Ok(mut context) = context_params.contexts.get_mut(window) {
+            let keys_down = context.ctx.get_mut().input(|i| i.keys_down.clone());
+            let egui_input = &mut context.egui_input;
+            egui_input.modifiers = egui::Modifiers::default();
+            egui_input
+                .events
+                .extend(keys_down.into_iter().map(|key| egui::Event::Key {
+                    key,
+                    pressed: false,
+                    repeat: false,
+                    modifiers: egui::Modifiers::default(),
+                }));
+            egui_input.events.push(egui::Event::PointerGone);
+        }
     }
 
     for mut context in context_params.contexts.iter_mut() {
-        context.egui_input.predicted_dt = time.raw_delta_seconds();
+        match context.time_source.copied().unwrap_or_default() {
+            EguiTimeSource::Real => {
+                context.egui_input.predicted_dt = time.raw_delta_seconds();
+            }
+            EguiTimeSource::Virtual => {
+                context.egui_input.time = Some(time.elapsed_seconds_f64());
+                context.egui_input.predicted_dt = time.delta_seconds();
+            }
+        }
     }
 
     // In some cases, we may skip certain events. For example, we ignore `ReceivedCharacter` events
@@ -281,23 +722,226 @@ pub fn process_input_system(
     input_events.clear();
 }
 
+/// Passes [`EguiInputEvent`] events to the contexts.
+pub fn process_input_events_system(
+    mut input_events: EventReader<EguiInputEvent>,
+    mut contexts: Query<&mut EguiInput, Without<EguiDisabled>>,
+) {
+    for EguiInputEvent { window, event } in input_events.iter() {
+        if let Ok(mut egui_input) = contexts.get_mut(*window) {
+            egui_input.events.push(event.clone());
+        }
+    }
+}
+
+/// Tracks the pointer and the primary button release for [`EguiDragAndDrop`].
+pub fn update_drag_and_drop_system(
+    mut drag_and_drop: ResMut<EguiDragAndDrop>,
+    egui_mouse_position: Res<EguiMousePosition>,
+    mouse_button_input: Option<Res<Input<MouseButton>>>,
+) {
+    // The item could be taken by a drop target during the frame of the release.
+    if drag_and_drop.released {
+        drag_and_drop.cancel();
+    }
+    drag_and_drop.hovered = egui_mouse_position.map(|(window, pos)| (window, pos.to_pos2()));
+    let released = mouse_button_input.is_some_and(|input| input.just_released(MouseButton::Left));
+    if drag_and_drop.is_dragging() && released {
+        drag_and_drop.released = true;
+    }
+}
+
+/// Translates gamepad input into Egui navigation, see [`EguiGamepadBindings`].
+pub fn process_gamepad_input_system(
+    gamepad_bindings: Res<EguiGamepadBindings>,
+    gamepads: Option<Res<Gamepads>>,
+    gamepad_buttons: Option<Res<Input<GamepadButton>>>,
+    gamepad_axes: Option<Res<Axis<GamepadAxis>>>,
+    mut prev_stick_action: Local<Option<EguiNavigationAction>>,
+    focused_window: Res<EguiFocusedWindow>,
+    mut contexts: Query<EguiContextQuery, Without<EguiDisabled>>,
+) {
+    let mut actions = Vec::new();
+
+    if let Some(gamepad_buttons) = &gamepad_buttons {
+        actions.extend(
+            gamepad_buttons
+                .get_just_pressed()
+                .filter_map(|button| gamepad_bindings.buttons.get(&button.button_type))
+                .copied(),
+        );
+    }
+
+    // Sticks trigger an action only once when tilted, like pressing a button.
+    if let (Some(threshold), Some(gamepads), Some(gamepad_axes)) =
+        (gamepad_bindings.stick_threshold, &gamepads, &gamepad_axes)
+    {
+        let stick_action = gamepads.iter().find_map(|gamepad| {
+            let axis = |axis_type| {
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, axis_type))
+                    .unwrap_or(0.0)
+            };
+            let (x, y) = (
+                axis(GamepadAxisType::LeftStickX),
+                axis(GamepadAxisType::LeftStickY),
+            );
+            if y >= threshold {
+                Some(EguiNavigationAction::Previous)
+            } else if y <= -threshold {
+                Some(EguiNavigationAction::Next)
+            } else if x <= -threshold {
+                Some(EguiNavigationAction::Left)
+            } else if x >= threshold {
+                Some(EguiNavigationAction::Right)
+            } else {
+                None
+            }
+        });
+        if stick_action != *prev_stick_action {
+            actions.extend(stick_action);
+        }
+        *prev_stick_action = stick_action;
+    }
+
+    if actions.is_empty() {
+        return;
+    }
+
+    // Actions go to the same context as keyboard input.
+    let Some(mut context) = focused_window
+        .and_then(|window| contexts.get_mut(window).ok())
+        .filter(|context| context.input_enabled.copied().unwrap_or_default().keyboard)
+    else {
+        return;
+    };
+    for action in actions {
+        let (key, modifiers) = action.key();
+        for pressed in [true, false] {
+            context.egui_input.events.push(egui::Event::Key {
+                key,
+                pressed,
+                repeat: false,
+                modifiers,
+            });
+        }
+    }
+}
+
+/// Keeps the contexts that follow cameras in sync with them, see [`EguiTargetCamera`]. Contexts
+/// of cameras with viewports (split-screen cameras) are restricted to the viewports as well.
+#[allow(clippy::type_complexity)]
+pub fn update_target_camera_contexts_system(
+    mut commands: Commands,
+    contexts: Query<(
+        Entity,
+        &EguiTargetCamera,
+        Option<&EguiViewport>,
+        Option<&EguiDisabled>,
+    )>,
+    cameras: Query<&Camera>,
+    camera_contexts: Query<
+        (Entity, &Camera, Option<&EguiViewport>),
+        (With<EguiContext>, Without<EguiTargetCamera>),
+    >,
+    mut disabled_by_camera: Local<HashSet<Entity>>,
+) {
+    // Contexts that stopped following a camera are enabled back.
+    disabled_by_camera.retain(|&entity| {
+        if contexts.contains(entity) {
+            return true;
+        }
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<EguiDisabled>();
+        }
+        false
+    });
+
+    for (entity, target_camera, viewport, disabled) in contexts.iter() {
+        let Some(camera) = cameras
+            .get(target_camera.0)
+            .ok()
+            .filter(|camera| camera.is_active)
+        else {
+            // Contexts that are disabled by users are left as they are.
+            if disabled.is_none() {
+                commands.entity(entity).insert(EguiDisabled);
+                disabled_by_camera.insert(entity);
+            }
+            continue;
+        };
+        if disabled_by_camera.remove(&entity) {
+            commands.entity(entity).remove::<EguiDisabled>();
+        }
+        update_camera_viewport(&mut commands, entity, camera, viewport);
+    }
+
+    for (entity, camera, viewport) in camera_contexts.iter() {
+        if camera.viewport.is_some() {
+            update_camera_viewport(&mut commands, entity, camera, viewport);
+        }
+    }
+}
+
+fn update_camera_viewport(
+    commands: &mut Commands,
+    context_entity: Entity,
+    camera: &Camera,
+    viewport: Option<&EguiViewport>,
+) {
+    if let Some((min, max)) = camera.logical_viewport_rect() {
+        let camera_viewport = EguiViewport(Rect::from_corners(min, max));
+        if viewport != Some(&camera_viewport) {
+            commands.entity(context_entity).insert(camera_viewport);
+        }
+    }
+}
+
 /// Initialises Egui contexts (for multiple windows).
 pub fn update_window_contexts_system(
     mut context_params: ContextSystemParams,
     egui_settings: Res<EguiSettings>,
+    render_device: Option<Res<RenderDevice>>,
+    mut egui_mouse_position: ResMut<EguiMousePosition>,
 ) {
+    let max_texture_side = egui_settings.effective_max_texture_side(render_device.as_deref());
     for mut context in context_params.contexts.iter_mut() {
-        let new_window_size = WindowSize::new(
-            context.window.physical_width() as f32,
-            context.window.physical_height() as f32,
-            context.window.scale_factor() as f32,
-        );
-        let width = new_window_size.physical_width
-            / new_window_size.scale_factor
-            / egui_settings.scale_factor as f32;
-        let height = new_window_size.physical_height
-            / new_window_size.scale_factor
-            / egui_settings.scale_factor as f32;
+        let mut new_window_size = match (&context.window, context.camera) {
+            (Some(window), _) => WindowSize::new(
+                window.physical_width() as f32,
+                window.physical_height() as f32,
+                window.scale_factor() as f32,
+            ),
+            // Contexts attached to cameras follow the size of their render targets.
+            (None, Some(camera)) => {
+                match (camera.physical_target_size(), camera.logical_target_size()) {
+                    (Some(physical_size), Some(logical_size)) if logical_size.x > 0.0 => {
+                        WindowSize::new(
+                            physical_size.x as f32,
+                            physical_size.y as f32,
+                            physical_size.x as f32 / logical_size.x,
+                        )
+                    }
+                    _ => continue,
+                }
+            }
+            // Contexts that aren't attached to windows are sized manually.
+            (None, None) => *context.window_size,
+        };
+        if new_window_size.scale_factor <= 0.0 {
+            continue;
+        }
+        new_window_size.zoom_factor = context.zoom_factor.map_or(1.0, |zoom_factor| zoom_factor.0);
+        let (physical_width, physical_height) = match context.viewport {
+            Some(viewport) => viewport.physical_rect(new_window_size).size().into(),
+            None => (
+                new_window_size.physical_width,
+                new_window_size.physical_height,
+            ),
+        };
+        let scale_factor = egui_settings.effective_scale_factor(new_window_size);
+        let width = physical_width / new_window_size.scale_factor / scale_factor;
+        let height = physical_height / new_window_size.scale_factor / scale_factor;
 
         if width < 1.0 || height < 1.0 {
             continue;
@@ -308,33 +952,193 @@ pub fn update_window_contexts_system(
             egui::pos2(width, height),
         ));
 
-        context.egui_input.pixels_per_point =
-            Some(new_window_size.scale_factor * egui_settings.scale_factor as f32);
+        context.egui_input.pixels_per_point = Some(new_window_size.scale_factor * scale_factor);
+        context.egui_input.max_texture_side = max_texture_side;
+
+        // Seed the pointer position of new contexts from the window, so that the first frame's
+        // hover is correct and clicks aren't lost if the cursor hasn't moved yet.
+        let cursor_position = context
+            .window
+            .as_ref()
+            .and_then(|window| window.cursor_position())
+            .filter(|_| context.egui_input.is_added());
+        if let Some(cursor_position) = cursor_position {
+            let pos = crate::window_to_egui_pos(
+                &egui_settings,
+                new_window_size,
+                context.viewport,
+                cursor_position,
+            );
+            if egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width, height)).contains(pos)
+            {
+                context
+                    .egui_input
+                    .events
+                    .push(egui::Event::PointerMoved(pos));
+                egui_mouse_position.0 = Some((context.window_entity, pos.to_vec2()));
+            }
+        }
 
         *context.window_size = new_window_size;
     }
 }
 
 /// Marks frame start for Egui.
-pub fn begin_frame_system(mut contexts: Query<(&mut EguiContext, &mut EguiInput)>) {
-    for (mut ctx, mut egui_input) in contexts.iter_mut() {
-        ctx.get_mut().begin_frame(egui_input.take());
+#[allow(clippy::type_complexity)]
+pub fn begin_frame_system(
+    mut contexts: Query<
+        (
+            Entity,
+            &mut EguiContext,
+            &mut EguiInput,
+            &mut WindowSize,
+            Option<&mut EguiZoomFactor>,
+        ),
+        Without<EguiDisabled>,
+    >,
+    egui_settings: Res<EguiSettings>,
+    event_filter: Option<Res<EguiEventFilter>>,
+    mut egui_frame_stats: ResMut<EguiFrameStats>,
+) {
+    egui_frame_stats.begin_frame();
+    for (window, mut ctx, mut egui_input, mut window_size, zoom_factor) in contexts.iter_mut() {
+        let mut raw_input = egui_input.take();
+        if let Some(event_filter) = &event_filter {
+            raw_input
+                .events
+                .retain(|event| (event_filter.0)(window, event));
+        }
+        if let Some(raw_input_hook) = &egui_settings.raw_input_hook {
+            (raw_input_hook.0)(window, &mut raw_input);
+        }
+        let pixels_per_point = raw_input.pixels_per_point;
+        let ctx = ctx.get_mut();
+        ctx.begin_frame(raw_input);
+
+        // Egui applies the zoom requested during the previous frame instead of the passed
+        // `pixels_per_point`, the zoom is stored to persist it.
+        let (Some(pixels_per_point), Some(mut zoom_factor)) = (pixels_per_point, zoom_factor)
+        else {
+            continue;
+        };
+        let new_pixels_per_point = ctx.pixels_per_point();
+        if new_pixels_per_point != pixels_per_point && pixels_per_point > 0.0 {
+            zoom_factor.0 *= new_pixels_per_point / pixels_per_point;
+            window_size.zoom_factor = zoom_factor.0;
+        }
+    }
+}
+
+/// Draws [`EguiInputDebugOverlay`] into the contexts.
+pub fn draw_input_debug_overlay_system(
+    mut overlay: ResMut<EguiInputDebugOverlay>,
+    mut contexts: Query<(Entity, &mut EguiContext), Without<EguiDisabled>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let overlay = &mut *overlay;
+    overlay
+        .recent_events
+        .retain(|window, _| contexts.contains(*window));
+    for (window, mut ctx) in contexts.iter_mut() {
+        let ctx = ctx.get_mut();
+        let raw_input = ctx.input(|input| input.raw.clone());
+        let recent_events = overlay.recent_events.entry(window).or_default();
+        recent_events.extend(
+            raw_input
+                .events
+                .into_iter()
+                .filter(|event| !matches!(event, egui::Event::PointerMoved(_))),
+        );
+        let overflow = recent_events.len().saturating_sub(overlay.max_events);
+        recent_events.drain(..overflow);
+
+        egui::Window::new("bevy_egui input").show(ctx, |ui| {
+            let pointer_pos = ctx.input(|input| input.pointer.hover_pos());
+            ui.label(format!("Pointer position: {pointer_pos:?}"));
+            ui.label(format!("Modifiers: {:?}", raw_input.modifiers));
+            ui.label(format!("Has focus: {}", raw_input.has_focus));
+            ui.label(format!(
+                "Pixels per point: {:?}",
+                raw_input.pixels_per_point
+            ));
+            ui.label(format!("Screen rect: {:?}", raw_input.screen_rect));
+            ui.separator();
+            ui.label("Recent events (latest first):");
+            for event in recent_events.iter().rev() {
+                ui.monospace(format!("{event:?}"));
+            }
+        });
+    }
+}
+
+/// Advances the time of contexts by the fixed timestep, see [`crate::EguiPlugin::run_in_fixed_update`].
+pub fn apply_fixed_time_system(
+    fixed_time: Option<Res<FixedTime>>,
+    mut contexts: Query<&mut EguiInput, Without<EguiDisabled>>,
+) {
+    let Some(fixed_time) = fixed_time else {
+        return;
+    };
+    for mut egui_input in contexts.iter_mut() {
+        // Egui advances its time by the predicted delta if the time isn't set.
+        egui_input.time = None;
+        egui_input.predicted_dt = fixed_time.period.as_secs_f32();
+    }
+}
+
+/// Marks frame end for Egui, its output is processed by [`process_output_system`].
+pub fn end_frame_system(
+    mut contexts: Query<(&mut EguiContext, &mut EguiFullOutput), Without<EguiDisabled>>,
+) {
+    for (mut ctx, mut full_output) in contexts.iter_mut() {
+        let output = ctx.get_mut().end_frame();
+        match &mut full_output.0 {
+            Some(full_output) => full_output.append(output),
+            None => full_output.0 = Some(output),
+        }
     }
 }
 
 /// Reads Egui output.
+#[allow(clippy::too_many_arguments)]
 pub fn process_output_system(
-    #[cfg_attr(not(feature = "open_url"), allow(unused_variables))] egui_settings: Res<
-        EguiSettings,
-    >,
+    egui_settings: Res<EguiSettings>,
     mut contexts: Query<EguiContextQuery>,
     #[cfg(feature = "manage_clipboard")] mut egui_clipboard: ResMut<crate::EguiClipboard>,
-    mut event: EventWriter<RequestRedraw>,
-    #[cfg(windows)] mut last_cursor_icon: Local<bevy::utils::HashMap<Entity, egui::CursorIcon>>,
+    mut request_redraw: Option<ResMut<Events<RequestRedraw>>>,
+    mut egui_frame_stats: ResMut<EguiFrameStats>,
+    egui_draw_calls: Res<EguiDrawCalls>,
+    mut egui_repaint: ResMut<EguiRepaint>,
+    mut repaint_requested: EventWriter<EguiRepaintRequested>,
+    mut egui_copy: EventWriter<EguiCopyEvent>,
+    mut output_events: EventWriter<EguiOutputEvent>,
+    mut ime_requests: EventWriter<EguiImeRequest>,
+    mut last_ime_requests: Local<bevy::utils::HashMap<Entity, EguiImeRequest>>,
+    mut last_cursor_icon: Local<bevy::utils::HashMap<Entity, egui::CursorIcon>>,
 ) {
+    // Forget the state of removed contexts (including the contexts of closed windows).
+    last_ime_requests.retain(|window, _| contexts.contains(*window));
+    last_cursor_icon.retain(|window, _| contexts.contains(*window));
+
+    let egui_frame_stats = &mut *egui_frame_stats;
+    let draw_calls = egui_draw_calls.0.lock().unwrap();
+    egui_frame_stats.context_count = 0;
+    egui_frame_stats.vertices = 0;
+    egui_frame_stats.indices = 0;
+    egui_frame_stats.draw_calls = draw_calls.values().sum();
+    egui_frame_stats.contexts.clear();
+
+    let now = Instant::now();
+    let egui_repaint = &mut *egui_repaint;
+    let mut min_repaint_after = Duration::MAX;
+    let mut repaint = false;
     for mut context in contexts.iter_mut() {
+        let Some(full_output) = context.full_output.0.take() else {
+            continue;
+        };
         let ctx = context.ctx.get_mut();
-        let full_output = ctx.end_frame();
         let egui::FullOutput {
             platform_output,
             shapes,
@@ -342,35 +1146,119 @@ pub fn process_output_system(
             repaint_after,
         } = full_output;
         let paint_jobs = ctx.tessellate(shapes);
+        let wants_pointer_input = ctx.wants_pointer_input();
+
+        let mut context_stats = EguiContextFrameStats {
+            draw_calls: draw_calls.get(&context.window_entity).copied().unwrap_or(0),
+            ..Default::default()
+        };
+        for paint_job in &paint_jobs {
+            if let egui::epaint::Primitive::Mesh(mesh) = &paint_job.primitive {
+                context_stats.vertices += mesh.vertices.len();
+                context_stats.indices += mesh.indices.len();
+            }
+        }
+        egui_frame_stats.context_count += 1;
+        egui_frame_stats.vertices += context_stats.vertices;
+        egui_frame_stats.indices += context_stats.indices;
+        egui_frame_stats
+            .contexts
+            .insert(context.window_entity, context_stats);
 
         context.render_output.paint_jobs = paint_jobs;
         context.render_output.textures_delta.append(textures_delta);
 
         context.egui_output.platform_output = platform_output.clone();
+        context.egui_output.used_rect = ctx.used_rect();
+
+        output_events.send_batch(platform_output.events.iter().map(|event| EguiOutputEvent {
+            window: context.window_entity,
+            event: event.clone(),
+        }));
 
-        #[cfg(feature = "manage_clipboard")]
         if !platform_output.copied_text.is_empty() {
+            #[cfg(feature = "manage_clipboard")]
             egui_clipboard.set_contents(&platform_output.copied_text);
+            egui_copy.send(EguiCopyEvent {
+                window: context.window_entity,
+                text: platform_output.copied_text.clone(),
+            });
         }
 
-        let mut set_icon = || {
-            context.window.cursor.icon = egui_to_winit_cursor_icon(platform_output.cursor_icon)
-                .unwrap_or(bevy::window::CursorIcon::Default);
+        // Egui reports the text cursor while a text field is focused, which is when the IME
+        // should be enabled.
+        let ime_request = EguiImeRequest {
+            window: context.window_entity,
+            enabled: platform_output.text_cursor_pos.is_some()
+                && context.input_enabled.copied().unwrap_or_default().keyboard,
+            cursor_position: platform_output.text_cursor_pos.map(|text_cursor_pos| {
+                let viewport_min = context
+                    .viewport
+                    .map_or(Vec2::ZERO, |viewport| viewport.0.min);
+                viewport_min
+                    + Vec2::new(text_cursor_pos.x, text_cursor_pos.y)
+                        * egui_settings.effective_scale_factor(*context.window_size)
+            }),
         };
+        if last_ime_requests.get(&context.window_entity) != Some(&ime_request) {
+            last_ime_requests.insert(context.window_entity, ime_request);
+            ime_requests.send(ime_request);
+        }
 
-        #[cfg(windows)]
-        {
+        if let Some(window) = context.window.as_mut() {
+            // The cursor is left to the app while it's grabbed or isn't used by Egui. The icon
+            // is only set when it changes, so that Egui resets it once when it stops using
+            // the pointer, and doesn't override the icons set by the app afterwards. This also
+            // avoids cursor flickering on Windows.
+            let grabbed = window.cursor.grab_mode != CursorGrabMode::None;
+            let cursor_icon = if egui_settings.force_cursor_icon || !grabbed && wants_pointer_input
+            {
+                platform_output.cursor_icon
+            } else {
+                egui::CursorIcon::Default
+            };
             let last_cursor_icon = last_cursor_icon.entry(context.window_entity).or_default();
-            if *last_cursor_icon != platform_output.cursor_icon {
-                set_icon();
-                *last_cursor_icon = platform_output.cursor_icon;
+            if *last_cursor_icon != cursor_icon {
+                window.cursor.icon = egui_to_winit_cursor_icon(cursor_icon)
+                    .unwrap_or(bevy::window::CursorIcon::Default);
+                *last_cursor_icon = cursor_icon;
+            }
+
+            if egui_settings.manage_ime {
+                if window.ime_enabled != ime_request.enabled {
+                    window.ime_enabled = ime_request.enabled;
+                }
+                // Keep the IME candidate window next to the text cursor.
+                if let Some(ime_position) = ime_request.cursor_position {
+                    if window.ime_position != ime_position {
+                        window.ime_position = ime_position;
+                    }
+                }
             }
         }
-        #[cfg(not(windows))]
-        set_icon();
 
-        if repaint_after.is_zero() {
-            event.send(RequestRedraw)
+        min_repaint_after = min_repaint_after.min(repaint_after);
+        // Either the context wants to repaint right away, or a repaint that was scheduled by
+        // a previous frame is due.
+        let repaint_due = egui_repaint
+            .deadlines
+            .get(&context.window_entity)
+            .is_some_and(|deadline| *deadline <= now);
+        if repaint_after.is_zero() || repaint_due {
+            repaint_requested.send(EguiRepaintRequested {
+                window: context.window_entity,
+            });
+            repaint = true;
+        }
+        match now.checked_add(repaint_after) {
+            Some(deadline) => {
+                egui_repaint
+                    .deadlines
+                    .insert(context.window_entity, deadline);
+            }
+            None => {
+                egui_repaint.deadlines.remove(&context.window_entity);
+            }
         }
 
         #[cfg(feature = "open_url")]
@@ -392,6 +1280,71 @@ pub fn process_output_system(
             }
         }
     }
+
+    egui_repaint.repaint_after = min_repaint_after;
+    egui_repaint.deadline = now.checked_add(min_repaint_after);
+    if repaint {
+        if let Some(request_redraw) = request_redraw.as_mut() {
+            request_redraw.send(RequestRedraw);
+        }
+    }
+
+    egui_frame_stats.cpu_run_time = egui_frame_stats
+        .frame_start
+        .take()
+        .map_or(Duration::ZERO, |frame_start| frame_start.elapsed());
+}
+
+/// The `max_wait` durations of [`WinitSettings`] configured by the user and the ones applied by
+/// [`wake_up_for_repaint_system`], for the focused and unfocused update modes.
+#[cfg(feature = "reactive_repaint")]
+#[derive(Default)]
+pub struct EguiWinitMaxWait {
+    user: [Option<Duration>; 2],
+    applied: [Option<Duration>; 2],
+}
+
+/// Shortens the `max_wait` of reactive [`WinitSettings`] update modes, so that the event loop
+/// wakes up when a repaint requested with [`egui::Context::request_repaint_after`] is due.
+///
+/// The durations configured by the user are restored once Egui is idle.
+#[cfg(feature = "reactive_repaint")]
+pub fn wake_up_for_repaint_system(
+    winit_settings: Option<ResMut<WinitSettings>>,
+    egui_repaint: Res<EguiRepaint>,
+    mut max_waits: Local<EguiWinitMaxWait>,
+) {
+    let Some(mut winit_settings) = winit_settings else {
+        return;
+    };
+    let until_deadline = egui_repaint
+        .deadline
+        .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+    let winit_settings = &mut *winit_settings;
+    let update_modes = [
+        &mut winit_settings.focused_mode,
+        &mut winit_settings.unfocused_mode,
+    ];
+    for (i, update_mode) in update_modes.into_iter().enumerate() {
+        let mode_max_wait = match update_mode {
+            UpdateMode::Reactive { max_wait } | UpdateMode::ReactiveLowPower { max_wait } => {
+                max_wait
+            }
+            UpdateMode::Continuous => {
+                max_waits.user[i] = None;
+                max_waits.applied[i] = None;
+                continue;
+            }
+        };
+        // Anything else than what was applied the last time has been set by the user.
+        if max_waits.applied[i] != Some(*mode_max_wait) {
+            max_waits.user[i] = Some(*mode_max_wait);
+        }
+        let user_max_wait = max_waits.user[i].unwrap_or(*mode_max_wait);
+        let new_max_wait = until_deadline.map_or(user_max_wait, |until| until.min(user_max_wait));
+        *mode_max_wait = new_max_wait;
+        max_waits.applied[i] = Some(new_max_wait);
+    }
 }
 
 fn egui_to_winit_cursor_icon(cursor_icon: egui::CursorIcon) -> Option<bevy::window::CursorIcon> {
@@ -434,6 +1387,35 @@ fn egui_to_winit_cursor_icon(cursor_icon: egui::CursorIcon) -> Option<bevy::wind
     }
 }
 
+/// Returns the window a context is drawn into: the window of the context itself, or the window
+/// its camera renders into. Contexts that aren't attached to windows or cameras are matched by
+/// their own entity.
+fn context_target_window(
+    context: &EguiContextQueryItem,
+    primary_window: Option<Entity>,
+) -> Option<Entity> {
+    let camera = match context.camera {
+        Some(camera) if context.window.is_none() => camera,
+        _ => return Some(context.window_entity),
+    };
+    if !camera.is_active {
+        return None;
+    }
+    match camera.target.normalize(primary_window)? {
+        NormalizedRenderTarget::Window(window) => Some(window.entity()),
+        NormalizedRenderTarget::Image(_) => None,
+    }
+}
+
+/// Filters out control characters, and the characters of the private use areas, which some
+/// platforms (for example, macOS) report for function and arrow keys.
+fn is_printable_char(chr: char) -> bool {
+    let is_in_private_use_area = ('\u{e000}'..='\u{f8ff}').contains(&chr)
+        || ('\u{f0000}'..='\u{ffffd}').contains(&chr)
+        || ('\u{100000}'..='\u{10fffd}').contains(&chr);
+    !is_in_private_use_area && !chr.is_control()
+}
+
 fn bevy_to_egui_key(key_code: KeyCode) -> Option<egui::Key> {
     let key = match key_code {
         KeyCode::Down => egui::Key::ArrowDown,
@@ -452,6 +1434,11 @@ fn bevy_to_egui_key(key_code: KeyCode) -> Option<egui::Key> {
         KeyCode::End => egui::Key::End,
         KeyCode::PageUp => egui::Key::PageUp,
         KeyCode::PageDown => egui::Key::PageDown,
+        // Shifted symbols map to the keys that type them, shift is reported via modifiers.
+        KeyCode::Minus | KeyCode::NumpadSubtract => egui::Key::Minus,
+        KeyCode::Equals | KeyCode::Plus | KeyCode::NumpadAdd | KeyCode::NumpadEquals => {
+            egui::Key::PlusEquals
+        }
         KeyCode::Numpad0 | KeyCode::Key0 => egui::Key::Num0,
         KeyCode::Numpad1 | KeyCode::Key1 => egui::Key::Num1,
         KeyCode::Numpad2 | KeyCode::Key2 => egui::Key::Num2,
@@ -492,3 +1479,578 @@ fn bevy_to_egui_key(key_code: KeyCode) -> Option<egui::Key> {
     };
     Some(key)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::test_app, EguiContexts, EguiFocusPriority, EguiRenderOrder, EguiTargetCamera,
+    };
+    use bevy::{
+        app::App,
+        ecs::system::Resource,
+        input::gamepad::{Gamepad, GamepadButtonChangedEvent, GamepadButtonType},
+        render::camera::RenderTarget,
+        time::TimeUpdateStrategy,
+        window::WindowRef,
+    };
+
+    #[test]
+    fn test_zoom_factor_persists() {
+        let (mut app, entity) = test_app();
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        ctx.get_mut().set_pixels_per_point(1.5);
+        app.update();
+        app.update();
+
+        assert_eq!(app.world.get::<EguiZoomFactor>(entity).unwrap().0, 1.5);
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        assert_eq!(ctx.get_mut().pixels_per_point(), 1.5);
+        assert_eq!(
+            ctx.get_mut().screen_rect(),
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(640.0 / 1.5, 480.0 / 1.5))
+        );
+    }
+
+    #[test]
+    fn test_disabled_context() {
+        let (mut app, entity) = test_app();
+        app.world.send_event(WindowFocused {
+            window: entity,
+            focused: true,
+        });
+        app.update();
+        let context_count = |app: &App| app.world.resource::<EguiFrameStats>().context_count;
+        assert_eq!(context_count(&app), 1);
+
+        app.world.entity_mut(entity).insert(EguiDisabled);
+        press_keys(&mut app, &[KeyCode::A]);
+        app.update();
+        assert_eq!(context_count(&app), 0);
+
+        // The input received while the context was disabled is discarded.
+        app.world.entity_mut(entity).remove::<EguiDisabled>();
+        app.update();
+        assert_eq!(context_count(&app), 1);
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        assert!(!ctx.get_mut().input(|i| i.key_down(egui::Key::A)));
+    }
+
+    #[test]
+    fn test_frame_time() {
+        let (mut app, entity) = test_app();
+        let virtual_context = app
+            .world
+            .spawn((
+                EguiContext::default(),
+                WindowSize::new(640.0, 480.0, 1.0),
+                EguiTimeSource::Virtual,
+            ))
+            .id();
+        app.update();
+        app.world.resource_mut::<Time>().set_relative_speed(0.5);
+        let frame_time = |app: &mut App, window| {
+            let mut ctx = app.world.get_mut::<EguiContext>(window).unwrap();
+            ctx.get_mut().input(|i| (i.time, i.unstable_dt))
+        };
+
+        let (mut real_time, _) = frame_time(&mut app, entity);
+        let (mut virtual_time, _) = frame_time(&mut app, virtual_context);
+        for dt in [0.01, 0.05, 0.02] {
+            let last_update = app.world.resource::<Time>().last_update().unwrap();
+            app.insert_resource(TimeUpdateStrategy::ManualInstant(
+                last_update + Duration::from_secs_f32(dt),
+            ));
+            app.update();
+
+            real_time += dt as f64;
+            let (time, unstable_dt) = frame_time(&mut app, entity);
+            assert!((time - real_time).abs() < 1e-4);
+            assert!((unstable_dt - dt).abs() < 1e-4);
+
+            virtual_time += dt as f64 * 0.5;
+            let (time, unstable_dt) = frame_time(&mut app, virtual_context);
+            assert!((time - virtual_time).abs() < 1e-4);
+            assert!((unstable_dt - dt * 0.5).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_modifiers_cleared_on_focus_loss() {
+        let (mut app, entity) = test_app();
+        let focus = |app: &mut App, focused| {
+            app.world.send_event(WindowFocused {
+                window: entity,
+                focused,
+            });
+        };
+        focus(&mut app, true);
+        press_keys(&mut app, &[KeyCode::LControl, KeyCode::A]);
+        app.update();
+        let input = |app: &mut App| {
+            let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+            ctx.get_mut()
+                .input(|i| (i.modifiers.ctrl, i.key_down(egui::Key::A)))
+        };
+        assert_eq!(input(&mut app), (true, true));
+
+        // The keys are released while another window is focused.
+        focus(&mut app, false);
+        app.update();
+        focus(&mut app, true);
+        app.update();
+        assert_eq!(input(&mut app), (false, false));
+    }
+
+    #[test]
+    fn test_received_characters() {
+        let (mut app, entity) = test_app();
+        // Characters typed with AZERTY and Dvorak layouts, and the character macOS reports for
+        // the up arrow key.
+        for char in ['é', 'ç', '\'', '\u{f700}'] {
+            app.world.send_event(ReceivedCharacter {
+                window: entity,
+                char,
+            });
+        }
+        app.update();
+
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        let text = ctx.get_mut().input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Text(text) => Some(text.clone()),
+                    _ => None,
+                })
+                .collect::<String>()
+        });
+        assert_eq!(text, "éç'");
+    }
+
+    #[test]
+    fn test_text_input_requires_focus() {
+        let (mut app, entity) = test_app();
+        app.world
+            .resource_mut::<EguiSettings>()
+            .text_input_requires_focus = true;
+        app.world.send_event(ReceivedCharacter {
+            window: entity,
+            char: 'w',
+        });
+        app.update();
+
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        let has_text = ctx.get_mut().input(|i| {
+            i.events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Text(_)))
+        });
+        assert!(!has_text);
+    }
+
+    #[test]
+    fn test_tab_requires_focus() {
+        let (mut app, entity) = test_app();
+        app.world.resource_mut::<EguiSettings>().tab_requires_focus = true;
+        app.world.send_event(WindowFocused {
+            window: entity,
+            focused: true,
+        });
+        let field_id = egui::Id::new("field");
+        app.add_system(move |mut contexts: EguiContexts| {
+            egui::CentralPanel::default().show(contexts.ctx_for_window_mut(entity), |ui| {
+                ui.add(egui::TextEdit::singleline(&mut String::new()).id(field_id));
+            });
+        });
+        let pressed_tab = |app: &mut App| {
+            press_keys(app, &[KeyCode::Tab]);
+            app.update();
+            let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+            ctx.get_mut().input(|i| i.key_pressed(egui::Key::Tab))
+        };
+
+        assert!(!pressed_tab(&mut app));
+        app.world
+            .get_mut::<EguiContext>(entity)
+            .unwrap()
+            .get_mut()
+            .memory_mut(|memory| memory.request_focus(field_id));
+        app.update();
+        assert!(pressed_tab(&mut app));
+    }
+
+    #[test]
+    fn test_shifted_keys() {
+        let (mut app, entity) = test_app();
+        app.world.send_event(WindowFocused {
+            window: entity,
+            focused: true,
+        });
+        // Shift + 1, Shift + = and Shift + A type "!", "+" and "A", but Egui receives the keys
+        // themselves, with the shift modifier.
+        press_keys(
+            &mut app,
+            &[KeyCode::LShift, KeyCode::Key1, KeyCode::Equals, KeyCode::A],
+        );
+        app.update();
+
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        let keys = ctx.get_mut().input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some((*key, modifiers.shift)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            keys,
+            [
+                (egui::Key::Num1, true),
+                (egui::Key::PlusEquals, true),
+                (egui::Key::A, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_focus_priority() {
+        let (mut app, low) = test_app();
+        let high = app
+            .world
+            .spawn((
+                EguiContext::default(),
+                WindowSize::new(640.0, 480.0, 1.0),
+                EguiFocusPriority(1),
+            ))
+            .id();
+        app.update();
+        for window in [high, low] {
+            app.world.send_event(WindowFocused {
+                window,
+                focused: true,
+            });
+        }
+        press_keys(&mut app, &[KeyCode::A]);
+        app.update();
+        let key_down = |app: &mut App, window| {
+            let mut ctx = app.world.get_mut::<EguiContext>(window).unwrap();
+            ctx.get_mut().input(|i| i.key_down(egui::Key::A))
+        };
+        assert!(key_down(&mut app, high));
+        assert!(!key_down(&mut app, low));
+    }
+
+    #[test]
+    fn test_gamepad_follows_focus() {
+        let (mut app, windowless) = test_app();
+        app.init_resource::<EguiGamepadBindings>();
+        let other = app
+            .world
+            .spawn((EguiContext::default(), WindowSize::new(640.0, 480.0, 1.0)))
+            .id();
+        // Contexts that aren't attached to windows can be focused by the app.
+        app.insert_resource(EguiFocusedWindow(Some(windowless)));
+        let press_south = |app: &mut App| {
+            let gamepad = Gamepad::new(0);
+            for value in [0.0, 1.0] {
+                app.world.send_event(GamepadButtonChangedEvent::new(
+                    gamepad,
+                    GamepadButtonType::South,
+                    value,
+                ));
+            }
+            app.update();
+        };
+        let activated = |app: &mut App, window| {
+            let mut ctx = app.world.get_mut::<EguiContext>(window).unwrap();
+            ctx.get_mut().input(|i| i.key_pressed(egui::Key::Enter))
+        };
+
+        press_south(&mut app);
+        assert!(activated(&mut app, windowless));
+        assert!(!activated(&mut app, other));
+
+        // Actions aren't passed to disabled contexts.
+        app.world.entity_mut(windowless).insert(EguiDisabled);
+        press_south(&mut app);
+        let egui_input = app.world.get::<EguiInput>(windowless).unwrap();
+        assert!(egui_input.events.is_empty());
+    }
+
+    #[test]
+    fn test_custom_input_events() {
+        let (mut app, entity) = test_app();
+        app.world.send_event(EguiInputEvent {
+            window: entity,
+            event: egui::Event::Key {
+                key: egui::Key::Space,
+                pressed: true,
+                repeat: false,
+                modifiers: egui::Modifiers::default(),
+            },
+        });
+        app.update();
+        let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+        assert!(ctx.get_mut().input(|i| i.key_down(egui::Key::Space)));
+    }
+
+    #[test]
+    fn test_camera_context_pointer() {
+        let (mut app, window) = test_app();
+        let camera = app
+            .world
+            .spawn((
+                Camera {
+                    target: RenderTarget::Window(WindowRef::Entity(window)),
+                    ..Default::default()
+                },
+                EguiContext::default(),
+                WindowSize::new(640.0, 480.0, 1.0),
+                EguiViewport(Rect::new(0.0, 0.0, 320.0, 480.0)),
+                EguiRenderOrder(1),
+            ))
+            .id();
+        app.update();
+        let hover_pos = |app: &mut App, context| {
+            let mut ctx = app.world.get_mut::<EguiContext>(context).unwrap();
+            ctx.get_mut().input(|i| i.pointer.hover_pos())
+        };
+
+        // The camera's context is drawn over the window's one.
+        app.world.send_event(CursorMoved {
+            window,
+            position: Vec2::new(100.0, 240.0),
+        });
+        app.update();
+        assert_eq!(hover_pos(&mut app, camera), Some(egui::pos2(100.0, 240.0)));
+        assert_eq!(hover_pos(&mut app, window), None);
+
+        app.world.send_event(CursorMoved {
+            window,
+            position: Vec2::new(400.0, 240.0),
+        });
+        app.update();
+        assert_eq!(hover_pos(&mut app, camera), None);
+        assert_eq!(hover_pos(&mut app, window), Some(egui::pos2(400.0, 240.0)));
+    }
+
+    #[test]
+    fn test_scroll_policy() {
+        let (mut app, entity) = test_app();
+        app.world.resource_mut::<EguiSettings>().scroll_policy = EguiScrollPolicy::WhenWantsPointer;
+        app.add_system(move |mut contexts: EguiContexts| {
+            egui::SidePanel::left("panel")
+                .exact_width(100.0)
+                .show(contexts.ctx_for_window_mut(entity), |_ui| {});
+        });
+        let scroll = |app: &mut App, x: f32| {
+            app.world.send_event(CursorMoved {
+                window: entity,
+                position: Vec2::new(x, 240.0),
+            });
+            app.update();
+            app.world.send_event(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y: 1.0,
+            });
+            app.update();
+            let mut ctx = app.world.get_mut::<EguiContext>(entity).unwrap();
+            let scrolled = ctx.get_mut().input(|i| i.scroll_delta) != egui::Vec2::ZERO;
+            (*app.world.resource::<EguiScrollCapture>(), scrolled)
+        };
+
+        assert_eq!(
+            scroll(&mut app, 50.0),
+            (EguiScrollCapture(Some(entity)), true)
+        );
+        assert_eq!(scroll(&mut app, 400.0), (EguiScrollCapture(None), false));
+
+        app.world.resource_mut::<EguiSettings>().scroll_policy = EguiScrollPolicy::Always;
+        assert_eq!(
+            scroll(&mut app, 400.0),
+            (EguiScrollCapture(Some(entity)), true)
+        );
+    }
+
+    #[test]
+    fn test_drag_between_contexts() {
+        let (mut app, source) = test_app();
+        let target = app
+            .world
+            .spawn((EguiContext::default(), WindowSize::new(640.0, 480.0, 1.0)))
+            .id();
+        let mouse_button = |app: &mut App, state| {
+            app.world.send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state,
+            });
+        };
+        mouse_button(&mut app, ButtonState::Pressed);
+        app.update();
+        app.world
+            .resource_mut::<EguiDragAndDrop>()
+            .start_drag(source, None, 42u32);
+
+        app.world.resource_mut::<EguiMousePosition>().0 = Some((target, egui::vec2(10.0, 10.0)));
+        mouse_button(&mut app, ButtonState::Released);
+        app.update();
+        let mut drag_and_drop = app.world.resource_mut::<EguiDragAndDrop>();
+        assert_eq!(
+            drag_and_drop.hovered(),
+            Some((target, egui::pos2(10.0, 10.0)))
+        );
+        assert!(drag_and_drop.take_dropped(source).is_none());
+        let dropped = drag_and_drop.take_dropped(target).unwrap();
+        assert_eq!(dropped.source_window, source);
+        assert_eq!(dropped.into_payload::<u32>(), Some(42));
+        assert!(!drag_and_drop.is_dragging());
+    }
+
+    #[test]
+    fn test_target_camera_activation() {
+        let (mut app, entity) = test_app();
+        let camera = app
+            .world
+            .spawn(Camera {
+                is_active: false,
+                ..Default::default()
+            })
+            .id();
+        app.world
+            .entity_mut(entity)
+            .insert(EguiTargetCamera(camera));
+        app.update();
+        let context_count = |app: &App| app.world.resource::<EguiFrameStats>().context_count;
+        assert_eq!(context_count(&app), 0);
+
+        app.world.get_mut::<Camera>(camera).unwrap().is_active = true;
+        app.update();
+        assert_eq!(context_count(&app), 1);
+
+        // Contexts disabled by users stay disabled.
+        app.world.entity_mut(entity).insert(EguiDisabled);
+        app.update();
+        assert_eq!(context_count(&app), 0);
+    }
+
+    #[test]
+    fn test_input_debug_overlay() {
+        let (mut app, entity) = test_app();
+        app.insert_resource(EguiInputDebugOverlay {
+            max_events: 2,
+            ..Default::default()
+        });
+        app.world.send_event(WindowFocused {
+            window: entity,
+            focused: true,
+        });
+        press_keys(&mut app, &[KeyCode::A, KeyCode::B, KeyCode::C]);
+        app.update();
+        let overlay = app.world.resource::<EguiInputDebugOverlay>();
+        let keys = overlay.recent_events[&entity]
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Key { key, .. } => Some(*key),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [egui::Key::B, egui::Key::C]);
+    }
+
+    #[cfg(feature = "reactive_repaint")]
+    #[test]
+    fn test_repaint_wakes_up_reactive_app() {
+        #[derive(Resource)]
+        struct RepaintAfter(Option<Duration>);
+
+        let (mut app, entity) = test_app();
+        app.insert_resource(WinitSettings::desktop_app())
+            .insert_resource(RepaintAfter(Some(Duration::from_millis(100))))
+            .add_system(
+                move |mut contexts: EguiContexts, repaint_after: Res<RepaintAfter>| {
+                    if let Some(repaint_after) = repaint_after.0 {
+                        contexts
+                            .ctx_for_window_mut(entity)
+                            .request_repaint_after(repaint_after);
+                    }
+                },
+            );
+        let max_wait = |app: &App| match app.world.resource::<WinitSettings>().focused_mode {
+            UpdateMode::Reactive { max_wait } | UpdateMode::ReactiveLowPower { max_wait } => {
+                max_wait
+            }
+            UpdateMode::Continuous => unreachable!(),
+        };
+
+        app.update();
+        assert!(max_wait(&app) <= Duration::from_millis(100));
+
+        // The user's settings are restored once Egui is idle, including the ones changed while
+        // the wait was shortened.
+        app.world.resource_mut::<WinitSettings>().focused_mode = UpdateMode::Reactive {
+            max_wait: Duration::from_secs(1),
+        };
+        app.update();
+        assert!(max_wait(&app) <= Duration::from_millis(100));
+        app.world.resource_mut::<RepaintAfter>().0 = None;
+        app.update();
+        app.update();
+        assert_eq!(max_wait(&app), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_spinner_repaint() {
+        #[derive(Resource)]
+        struct ShowSpinner(bool);
+
+        let (mut app, entity) = test_app();
+        app.insert_resource(ShowSpinner(true)).add_system(
+            move |mut contexts: EguiContexts, show: Res<ShowSpinner>| {
+                egui::CentralPanel::default().show(contexts.ctx_for_window_mut(entity), |ui| {
+                    if show.0 {
+                        ui.spinner();
+                    }
+                });
+            },
+        );
+        let redraw_requests = |app: &mut App| {
+            app.update();
+            let events = app.world.resource::<Events<RequestRedraw>>();
+            events.iter_current_update_events().count()
+        };
+        for _ in 0..3 {
+            assert_eq!(redraw_requests(&mut app), 1);
+            assert!(app.world.resource::<EguiRepaint>().repaint_after.is_zero());
+        }
+
+        // Once the spinner is hidden, Egui settles and stops requesting repaints.
+        app.world.resource_mut::<ShowSpinner>().0 = false;
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(redraw_requests(&mut app), 0);
+        assert_eq!(
+            app.world.resource::<EguiRepaint>().repaint_after,
+            Duration::MAX
+        );
+    }
+
+    fn press_keys(app: &mut App, key_codes: &[KeyCode]) {
+        for key_code in key_codes {
+            app.world.send_event(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(*key_code),
+                state: ButtonState::Pressed,
+            });
+        }
+    }
+}